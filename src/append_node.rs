@@ -12,11 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::audit::{audit, chain_config_hash};
+use crate::config::chain_config::check_node_list;
 use crate::config::chain_config::ConfigStage;
-use crate::config::chain_config::NodeNetworkAddressBuilder;
 use crate::constant::CHAIN_CONFIG_FILE;
 use crate::error::Error;
-use crate::util::{read_chain_config, write_toml};
+use crate::util::{parse_node, read_chain_config, write_toml};
 use clap::Parser;
 
 /// A subcommand for run
@@ -31,18 +32,21 @@ pub struct AppendNodeOpts {
     /// node network address looks like localhost:40002:node2:k8s_cluster_name:namespace
     /// k8s_cluster_name is optional, none means not k8s env.
     /// namespace is optional, none means default namespace.
+    /// protocol prefix is optional, quic/tls/tcp, none means quic, like tcp/localhost:40000:node0
     #[clap(long = "node")]
     pub node: String,
 }
 
 /// execute append node
 pub fn execute_append_node(opts: AppendNodeOpts) -> Result<(), Error> {
+    let before_hash = chain_config_hash(&opts.config_dir, &opts.chain_name);
     // load chain_config
     let file_name = format!(
         "{}/{}/{}",
         &opts.config_dir, &opts.chain_name, CHAIN_CONFIG_FILE
     );
     let mut chain_config = read_chain_config(&file_name).unwrap();
+    chain_config.check_genesis_hash()?;
 
     if chain_config.stage == ConfigStage::Init {
         return Err(Error::InvalidStage);
@@ -50,42 +54,23 @@ pub fn execute_append_node(opts: AppendNodeOpts) -> Result<(), Error> {
 
     let mut node_list = chain_config.node_network_address_list.clone();
 
-    let node_network_info: Vec<&str> = opts.node.split(':').collect();
-    if node_network_info.len() == 3 {
-        node_list.push(
-            NodeNetworkAddressBuilder::default()
-                .host(node_network_info[0].to_string())
-                .port(node_network_info[1].parse::<u16>().unwrap())
-                .domain(node_network_info[2].to_string())
-                .build(),
-        );
-    } else if node_network_info.len() == 4 {
-        node_list.push(
-            NodeNetworkAddressBuilder::default()
-                .host(node_network_info[0].to_string())
-                .port(node_network_info[1].parse::<u16>().unwrap())
-                .domain(node_network_info[2].to_string())
-                .cluster(node_network_info[3].to_string())
-                .build(),
-        );
-    } else if node_network_info.len() == 5 {
-        node_list.push(
-            NodeNetworkAddressBuilder::default()
-                .host(node_network_info[0].to_string())
-                .port(node_network_info[1].parse::<u16>().unwrap())
-                .domain(node_network_info[2].to_string())
-                .cluster(node_network_info[3].to_string())
-                .name_space(node_network_info[4].to_string())
-                .build(),
-        );
-    } else {
-        panic!("invalid node network address format!")
-    }
+    node_list.push(parse_node(&opts.node)?);
+    check_node_list(&node_list).map_err(|e| {
+        println!("{e}");
+        Error::InvalidNodeAddress
+    })?;
 
     chain_config.set_node_network_address_list(node_list);
 
     // store chain_config
     write_toml(&chain_config, file_name);
 
+    audit(
+        &opts.config_dir,
+        &opts.chain_name,
+        "append-node",
+        &before_hash,
+    );
+
     Ok(())
 }
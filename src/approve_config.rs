@@ -0,0 +1,257 @@
+// Copyright Rivtower Technologies LLC.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::audit::{audit, chain_config_hash};
+use crate::config::chain_config::{ChainConfig, ConfigStage};
+use crate::constant::{
+    ACCOUNT_DIR, APPROVAL_DIR, CHAIN_CONFIG_FILE, CONSENSUS_OVERLORD, PRIVATE_KEY,
+    VALIDATOR_ADDRESS,
+};
+use crate::error::Error;
+use crate::util::{
+    bls_sign_message, bls_verify, crypto_hash, find_micro_service, read_chain_config, read_file,
+    recover_address, remove_0x, sign_message, write_toml,
+};
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+
+/// A subcommand for run
+#[derive(Parser, Debug, Clone)]
+pub struct ApproveConfigOpts {
+    /// set chain name
+    #[clap(long = "chain-name", default_value = "test-chain")]
+    pub chain_name: String,
+    /// set config file directory, default means current directory
+    #[clap(long = "config-dir", default_value = ".")]
+    pub config_dir: String,
+    /// node account which private key is in accounts folder
+    #[clap(long = "account")]
+    pub account: String,
+}
+
+/// signature of a validator over the canonical chain config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Approval {
+    pub validator_address: String,
+    pub signature: String,
+}
+
+// bind validator address into signed message
+fn approval_message(chain_config: &ChainConfig, validator_address: &str) -> Vec<u8> {
+    let mut content = chain_config.approval_hash();
+    content.extend_from_slice(validator_address.as_bytes());
+    crypto_hash(&content)
+}
+
+impl Approval {
+    /// validator_address must be one of validators, and signature must be signed by its key:
+    /// bls signature of validator_address if consensus is overlord,
+    /// otherwise signer recovered from signature must be validator_address
+    pub fn verify(&self, chain_config: &ChainConfig) -> bool {
+        if !chain_config
+            .system_config
+            .validators
+            .contains(&self.validator_address)
+        {
+            return false;
+        }
+        let (Ok(validator_address), Ok(signature)) = (
+            hex::decode(&self.validator_address),
+            hex::decode(&self.signature),
+        ) else {
+            return false;
+        };
+        let msg = approval_message(chain_config, &self.validator_address);
+        if find_micro_service(chain_config, CONSENSUS_OVERLORD) {
+            bls_verify(&validator_address, &msg, &signature)
+        } else {
+            recover_address(&msg, &signature) == Some(validator_address)
+        }
+    }
+}
+
+/// count valid approvals stored alongside chain_config.toml
+/// each validator counts only once
+pub fn count_approvals(config_dir: &str, chain_name: &str, chain_config: &ChainConfig) -> usize {
+    let approval_dir = format!("{config_dir}/{chain_name}/{APPROVAL_DIR}");
+    let entries = match fs::read_dir(approval_dir) {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+
+    let mut approved_validators = HashSet::new();
+    for entry in entries.flatten() {
+        let approval = read_file(entry.path())
+            .ok()
+            .and_then(|s| toml::from_str::<Approval>(&s).ok());
+        match approval {
+            Some(approval) if approval.verify(chain_config) => {
+                approved_validators.insert(approval.validator_address);
+            }
+            _ => println!("ignore invalid approval: {:?}", entry.path()),
+        }
+    }
+    approved_validators.len()
+}
+
+/// execute approve config
+/// $(config_dir)
+/// --  $(chain_name)
+/// ------  approvals
+/// --------  $(node_address).toml
+pub fn execute_approve_config(opts: ApproveConfigOpts) -> Result<Approval, Error> {
+    let before_hash = chain_config_hash(&opts.config_dir, &opts.chain_name);
+    // load chain_config
+    let file_name = format!(
+        "{}/{}/{}",
+        &opts.config_dir, &opts.chain_name, CHAIN_CONFIG_FILE
+    );
+    let chain_config = read_chain_config(file_name).unwrap();
+    chain_config.check_genesis_hash()?;
+
+    // participants approve chain config before finalize
+    if chain_config.stage != ConfigStage::Public {
+        return Err(Error::InvalidStage);
+    }
+
+    let account = remove_0x(&opts.account);
+    let account_dir = format!(
+        "{}/{}/{}/{}",
+        &opts.config_dir, &opts.chain_name, ACCOUNT_DIR, account
+    );
+    let private_key = hex::decode(remove_0x(
+        read_file(format!("{account_dir}/{PRIVATE_KEY}"))
+            .unwrap()
+            .trim(),
+    ))
+    .expect("invalid private key");
+    let validator_address = read_file(format!("{account_dir}/{VALIDATOR_ADDRESS}"))
+        .unwrap()
+        .trim()
+        .to_string();
+
+    let msg = approval_message(&chain_config, &validator_address);
+    let signature = if find_micro_service(&chain_config, CONSENSUS_OVERLORD) {
+        bls_sign_message(&private_key, &msg)
+    } else {
+        sign_message(&private_key, &msg)
+    };
+
+    let approval = Approval {
+        validator_address,
+        signature: hex::encode(signature),
+    };
+
+    if !approval.verify(&chain_config) {
+        println!("account {account} is not a validator of chain");
+        return Err(Error::InvalidValidators);
+    }
+
+    let approval_dir = format!("{}/{}/{}", &opts.config_dir, &opts.chain_name, APPROVAL_DIR);
+    fs::create_dir_all(&approval_dir).unwrap();
+    write_toml(&approval, format!("{approval_dir}/{account}.toml"));
+
+    audit(
+        &opts.config_dir,
+        &opts.chain_name,
+        "approve-config",
+        &before_hash,
+    );
+
+    Ok(approval)
+}
+
+#[cfg(test)]
+mod approve_config_test {
+    use super::*;
+    use crate::config::chain_config::{ChainConfigBuilder, MicroServiceBuilder};
+    use crate::constant::CONSENSUS_RAFT;
+    use ophelia::{PublicKey, ToBlsPublicKey};
+    use ophelia_blst::BlsPrivateKey;
+
+    fn approve(chain_config: &ChainConfig, validator_address: &str, signature: Vec<u8>) -> bool {
+        Approval {
+            validator_address: validator_address.to_string(),
+            signature: hex::encode(signature),
+        }
+        .verify(chain_config)
+    }
+
+    #[test]
+    fn verify_test() {
+        let private_key = [1u8; 32];
+        let other_key = [2u8; 32];
+
+        // validator address is recovered from signature
+        let mut chain_config = ChainConfigBuilder::default()
+            .micro_service_list(vec![MicroServiceBuilder::default()
+                .image(CONSENSUS_RAFT.to_string())
+                .build()])
+            .build();
+        let validator = {
+            let msg = approval_message(&chain_config, "");
+            hex::encode(recover_address(&msg, &sign_message(&private_key, &msg)).unwrap())
+        };
+        chain_config.set_validators(vec![validator.clone()]);
+        let msg = approval_message(&chain_config, &validator);
+        assert!(approve(
+            &chain_config,
+            &validator,
+            sign_message(&private_key, &msg)
+        ));
+        // signed by key of someone else
+        assert!(!approve(
+            &chain_config,
+            &validator,
+            sign_message(&other_key, &msg)
+        ));
+        // not a validator
+        let msg = approval_message(&chain_config, &"cc".repeat(20));
+        assert!(!approve(
+            &chain_config,
+            &"cc".repeat(20),
+            sign_message(&private_key, &msg)
+        ));
+
+        // validator address is bls public key of overlord
+        let mut chain_config = ChainConfigBuilder::default()
+            .micro_service_list(vec![MicroServiceBuilder::default()
+                .image(CONSENSUS_OVERLORD.to_string())
+                .build()])
+            .build();
+        let validator = {
+            let private_key = BlsPrivateKey::try_from(&private_key[..]).unwrap();
+            hex::encode(private_key.pub_key(&"".to_string()).to_bytes())
+        };
+        chain_config.set_validators(vec![validator.clone()]);
+        let msg = approval_message(&chain_config, &validator);
+        assert!(approve(
+            &chain_config,
+            &validator,
+            bls_sign_message(&private_key, &msg)
+        ));
+        assert!(!approve(
+            &chain_config,
+            &validator,
+            bls_sign_message(&other_key, &msg)
+        ));
+        assert!(!approve(
+            &chain_config,
+            &validator,
+            sign_message(&private_key, &msg)
+        ));
+    }
+}
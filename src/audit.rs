@@ -0,0 +1,143 @@
+// Copyright Rivtower Technologies LLC.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::constant::{AUDIT_LOG_FILE, CHAIN_CONFIG_FILE};
+use crate::util::{sm3_hash, unix_now};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+use std::{env, fs};
+
+// values of these args will not be recorded
+const SECRET_ARGS: [&str; 3] = ["--privkey", "--access-key-id", "--secret-access-key"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub timestamp: u64,
+    pub user: String,
+    pub command: String,
+    // command line of the invocation, maybe a high level command like create
+    pub args: Vec<String>,
+    // sm3 hash of chain_config.toml, empty means not exists
+    pub before_hash: String,
+    pub after_hash: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AuditLog {
+    #[serde(default)]
+    pub record: Vec<AuditRecord>,
+}
+
+fn redact_args(args: impl Iterator<Item = String>) -> Vec<String> {
+    let mut redacted = Vec::new();
+    let mut is_secret = false;
+    for arg in args {
+        if is_secret {
+            redacted.push("***".to_string());
+            is_secret = false;
+            continue;
+        }
+        match arg.split_once('=') {
+            Some((name, _)) if SECRET_ARGS.contains(&name) => {
+                redacted.push(format!("{name}=***"));
+            }
+            _ => {
+                is_secret = SECRET_ARGS.contains(&arg.as_str());
+                redacted.push(arg);
+            }
+        }
+    }
+    redacted
+}
+
+pub fn chain_config_hash(config_dir: &str, chain_name: &str) -> String {
+    let file_name = format!("{config_dir}/{chain_name}/{CHAIN_CONFIG_FILE}");
+    match fs::read(file_name) {
+        Ok(content) => hex::encode(sm3_hash(&content)),
+        Err(_) => "".to_string(),
+    }
+}
+
+/// append a record into audit log of chain
+/// before_hash should be got by chain_config_hash before mutation
+pub fn audit(config_dir: &str, chain_name: &str, command: &str, before_hash: &str) {
+    let chain_dir = format!("{config_dir}/{chain_name}");
+    if !Path::new(&chain_dir).exists() {
+        return;
+    }
+
+    let user = env::var("USER")
+        .or_else(|_| env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string());
+    let audit_log = AuditLog {
+        record: vec![AuditRecord {
+            timestamp: unix_now(),
+            user,
+            command: command.to_string(),
+            args: redact_args(env::args()),
+            before_hash: before_hash.to_string(),
+            after_hash: chain_config_hash(config_dir, chain_name),
+        }],
+    };
+
+    // each record is a [[record]] table, so the whole file is valid toml
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(format!("{chain_dir}/{AUDIT_LOG_FILE}"))
+        .unwrap();
+    file.write_all(toml::to_string(&audit_log).unwrap().as_bytes())
+        .unwrap();
+    file.write_all(b"\n").unwrap();
+}
+
+pub fn read_audit_log(config_dir: &str, chain_name: &str) -> AuditLog {
+    let file_name = format!("{config_dir}/{chain_name}/{AUDIT_LOG_FILE}");
+    match fs::read_to_string(file_name) {
+        Ok(content) => toml::from_str::<AuditLog>(&content).unwrap(),
+        Err(_) => AuditLog::default(),
+    }
+}
+
+#[cfg(test)]
+mod audit_test {
+    use super::*;
+
+    #[test]
+    fn redact_test() {
+        let args = vec![
+            "cloud-config",
+            "import-account",
+            "--privkey",
+            "0x1234",
+            "--secret-access-key=abcd",
+            "--chain-name",
+            "test-chain",
+        ];
+        let redacted = redact_args(args.into_iter().map(|s| s.to_string()));
+        assert_eq!(
+            redacted,
+            vec![
+                "cloud-config",
+                "import-account",
+                "--privkey",
+                "***",
+                "--secret-access-key=***",
+                "--chain-name",
+                "test-chain",
+            ]
+        );
+    }
+}
@@ -14,8 +14,11 @@
 
 use crate::append_node::{execute_append_node, AppendNodeOpts};
 use crate::append_validator::{execute_append_validator, AppendValidatorOpts};
-use crate::config::chain_config::{NodeNetworkAddress, NodeNetworkAddressBuilder};
-use crate::constant::CHAIN_CONFIG_FILE;
+use crate::approve_config::{execute_approve_config, ApproveConfigOpts};
+use crate::config::chain_config::NodeRole;
+use crate::constant::{
+    CHAIN_CONFIG_FILE, DEFAULT_CHAINCODE_PORT, DEFAULT_INTERNAL_PORT, EXECUTOR_CHAINCODE,
+};
 use crate::create_ca::{execute_create_ca, CreateCAOpts};
 use crate::create_csr::{execute_create_csr, CreateCSROpts};
 use crate::delete_node::{delete_node_folders, execute_delete_node, DeleteNodeOpts};
@@ -24,12 +27,14 @@ use crate::init_chain::{execute_init_chain, InitChainOpts};
 use crate::init_chain_config::{execute_init_chain_config, InitChainConfigOpts};
 use crate::init_node::{execute_init_node, InitNodeOpts};
 use crate::new_account::{execute_new_account, NewAccountOpts};
+use crate::port_allocator::LocalPortAllocator;
 use crate::set_admin::{execute_set_admin, SetAdminOpts};
 use crate::set_nodelist::{execute_set_nodelist, SetNodeListOpts};
 use crate::set_stage::{execute_set_stage, SetStageOpts};
 use crate::sign_csr::{execute_sign_csr, SignCSROpts};
+use crate::traits::PortAllocator;
 use crate::update_node::{execute_update_node, UpdateNodeOpts};
-use crate::util::read_chain_config;
+use crate::util::{find_micro_service, load_node_list, parse_node, read_chain_config};
 use clap::Parser;
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -80,7 +85,7 @@ pub struct CreateOpts {
     /// set consensus micro service image tag
     #[clap(long = "consensus_tag", default_value = "latest")]
     pub consensus_tag: String,
-    /// set executor micro service image name (executor_evm)
+    /// set executor micro service image name (executor_evm/executor_chaincode)
     #[clap(long = "executor_image", default_value = "executor_evm")]
     pub executor_image: String,
     /// set executor micro service image tag
@@ -98,6 +103,9 @@ pub struct CreateOpts {
     /// set controller micro service image tag
     #[clap(long = "controller_tag", default_value = "latest")]
     pub controller_tag: String,
+    /// set network listen port of nodes in k8s
+    #[clap(long = "internal-port", default_value = "40000")]
+    pub internal_port: u16,
 
     /// set admin
     #[clap(long = "admin")]
@@ -107,8 +115,14 @@ pub struct CreateOpts {
     /// for each node network address:
     /// k8s_cluster_name is optional, none means not k8s env.
     /// namespace is optional, none means default namespace.
-    #[clap(long = "nodelist")]
+    /// protocol prefix is optional, quic/tls/tcp, none means quic, like tcp/localhost:40000:node0
+    /// ipv6 host must be bracketed like [::1]:40000:node0
+    /// or use key=value like host=localhost;port=40000;domain=node0;cluster=k8s;namespace=default
+    #[clap(long = "nodelist", default_value = "")]
     pub node_list: String,
+    /// node list file in yaml or json, override --nodelist
+    #[clap(long = "nodelist-file")]
+    pub node_list_file: Option<String>,
 
     /// log level
     #[clap(long = "log-level", default_value = "info")]
@@ -133,30 +147,39 @@ pub struct CreateOpts {
     #[clap(long = "disable-metrics")]
     pub disable_metrics: bool,
 
-    /// cloud_storage.access_key_id
+    /// storage_backend.access_key_id, account_name of azblob, env:NAME, file:PATH or secret:NAME/KEY refers to credential instead of plain text
     #[clap(long = "access-key-id", default_value = "")]
     pub access_key_id: String,
-    /// cloud_storage.secret_access_key
+    /// storage_backend.secret_access_key, account_key of azblob, env:NAME, file:PATH or secret:NAME/KEY refers to credential instead of plain text
     #[clap(long = "secret-access-key", default_value = "")]
     pub secret_access_key: String,
-    /// cloud_storage.endpoint
+    /// storage_backend.endpoint
     #[clap(long = "s3-endpoint", default_value = "")]
     pub s3_endpoint: String,
-    /// cloud_storage.bucket
+    /// storage_backend.bucket, container of azblob
     #[clap(long = "s3-bucket", default_value = "")]
     pub s3_bucket: String,
-    /// cloud_storage.service_type: s3/oss(aliyun)/obs(huawei)/cos(tencent)/azblob(azure)
+    /// storage_backend.type: fs/s3/oss(aliyun)/obs(huawei)/cos(tencent)/azblob(azure), empty means no cloud storage
     #[clap(long = "service-type", default_value = "")]
     pub service_type: String,
-    /// cloud_storage.root
+    /// storage_backend.root
     #[clap(long = "s3-root", default_value = "")]
     pub s3_root: String,
-    /// cloud_storage.region
+    /// storage_backend.region
     #[clap(long = "s3-region", default_value = "")]
     pub s3_region: String,
     /// exporter.base_path
     #[clap(long = "exporter-path", default_value = "")]
     pub exporter_path: String,
+    /// first port of grpc ports allocated for local nodes
+    #[clap(long = "grpc-port-base", default_value = "50000")]
+    pub grpc_port_base: u16,
+    /// first port of metrics ports allocated for local nodes
+    #[clap(long = "metrics-port-base", default_value = "60000")]
+    pub metrics_port_base: u16,
+    /// skip ports which are bound on this machine when allocate ports
+    #[clap(long = "check-port-bind")]
+    pub check_port_bind: bool,
 }
 
 impl Default for CreateOpts {
@@ -182,8 +205,10 @@ impl Default for CreateOpts {
             storage_tag: "latest".to_string(),
             controller_image: "controller_hsm".to_string(),
             controller_tag: "latest".to_string(),
+            internal_port: DEFAULT_INTERNAL_PORT,
             admin: Default::default(),
             node_list: Default::default(),
+            node_list_file: Default::default(),
             log_level: "info".to_string(),
             log_file_path: Default::default(),
             jaeger_agent_endpoint: Default::default(),
@@ -198,15 +223,22 @@ impl Default for CreateOpts {
             s3_root: "".to_string(),
             s3_region: "".to_string(),
             exporter_path: "".to_string(),
+            grpc_port_base: 50000,
+            metrics_port_base: 60000,
+            check_port_bind: false,
         }
     }
 }
 
 /// admin set by args
-/// grpc ports start from 50000
-/// node network listen port is 40000
+/// grpc ports of k8s node start from 50000
+/// grpc ports of local node allocated from grpc_port_base
+/// node network listen port is internal_port
 /// is stdout is true
 pub fn execute_create(opts: CreateOpts) -> Result<(), Error> {
+    // parse node list before anything created
+    let node_list = load_node_list(&opts.node_list, opts.node_list_file.as_deref())?;
+
     // init chain
     execute_init_chain(InitChainOpts {
         chain_name: opts.chain_name.clone(),
@@ -235,6 +267,8 @@ pub fn execute_create(opts: CreateOpts) -> Result<(), Error> {
         storage_tag: opts.storage_tag.clone(),
         controller_image: opts.controller_image.clone(),
         controller_tag: opts.controller_tag.clone(),
+        internal_port: opts.internal_port,
+        approval_quorum: None,
     })
     .unwrap();
 
@@ -246,53 +280,25 @@ pub fn execute_create(opts: CreateOpts) -> Result<(), Error> {
     })
     .unwrap();
 
-    // parse node list
-    let node_list_str: Vec<&str> = opts.node_list.split(',').collect();
-    let node_list: Vec<NodeNetworkAddress> = node_list_str
-        .iter()
-        .map(|node| {
-            let node_network_info: Vec<&str> = node.split(':').collect();
-            if node_network_info.len() == 3 {
-                NodeNetworkAddressBuilder::default()
-                    .host(node_network_info[0].to_string())
-                    .port(node_network_info[1].parse::<u16>().unwrap())
-                    .domain(node_network_info[2].to_string())
-                    .build()
-            } else if node_network_info.len() == 4 {
-                NodeNetworkAddressBuilder::default()
-                    .host(node_network_info[0].to_string())
-                    .port(node_network_info[1].parse::<u16>().unwrap())
-                    .domain(node_network_info[2].to_string())
-                    .cluster(node_network_info[3].to_string())
-                    .build()
-            } else if node_network_info.len() == 5 {
-                NodeNetworkAddressBuilder::default()
-                    .host(node_network_info[0].to_string())
-                    .port(node_network_info[1].parse::<u16>().unwrap())
-                    .domain(node_network_info[2].to_string())
-                    .cluster(node_network_info[3].to_string())
-                    .name_space(node_network_info[4].to_string())
-                    .build()
-            } else {
-                panic!("invalid node network address format!")
-            }
-        })
-        .collect();
-
     // gen validator addr and append validator
+    // observer has account but is not a validator
     let mut node_accounts = Vec::new();
-    for _ in 0..node_list.len() {
+    let mut validator_accounts = Vec::new();
+    for node in node_list.iter() {
         let (addr, validator_addr) = execute_new_account(NewAccountOpts {
             chain_name: opts.chain_name.clone(),
             config_dir: opts.config_dir.clone(),
         })
         .unwrap();
-        execute_append_validator(AppendValidatorOpts {
-            chain_name: opts.chain_name.clone(),
-            config_dir: opts.config_dir.clone(),
-            validator: validator_addr.clone(),
-        })
-        .unwrap();
+        if node.role == NodeRole::Validator {
+            execute_append_validator(AppendValidatorOpts {
+                chain_name: opts.chain_name.clone(),
+                config_dir: opts.config_dir.clone(),
+                validator: validator_addr.clone(),
+            })
+            .unwrap();
+            validator_accounts.push(addr.clone());
+        }
         node_accounts.push(addr);
     }
 
@@ -301,6 +307,7 @@ pub fn execute_create(opts: CreateOpts) -> Result<(), Error> {
         chain_name: opts.chain_name.clone(),
         config_dir: opts.config_dir.clone(),
         node_list: opts.node_list.clone(),
+        node_list_file: opts.node_list_file.clone(),
     })
     .unwrap();
 
@@ -326,6 +333,16 @@ pub fn execute_create(opts: CreateOpts) -> Result<(), Error> {
         .unwrap();
     }
 
+    // all validators approve chain config
+    for account in validator_accounts {
+        execute_approve_config(ApproveConfigOpts {
+            chain_name: opts.chain_name.clone(),
+            config_dir: opts.config_dir.clone(),
+            account,
+        })
+        .unwrap();
+    }
+
     execute_set_stage(SetStageOpts {
         chain_name: opts.chain_name.clone(),
         config_dir: opts.config_dir.clone(),
@@ -342,16 +359,23 @@ pub fn execute_create(opts: CreateOpts) -> Result<(), Error> {
     let chain_config = read_chain_config(chain_config_file).unwrap();
 
     // init node and update node
+    let mut port_allocator = LocalPortAllocator::new(
+        &opts.config_dir,
+        &opts.chain_name,
+        opts.grpc_port_base,
+        opts.metrics_port_base,
+        opts.check_port_bind,
+    );
+    let is_chaincode = find_micro_service(&chain_config, EXECUTOR_CHAINCODE);
     for (i, node) in chain_config.node_network_address_list.iter().enumerate() {
-        // for k8s node offset is 0
-        // for none k8s node offset used to avoid port conflict
-        let offset = if node.cluster.is_empty() {
-            (node.port - 40000) * 100
+        // ports of k8s node are inside pod
+        let (network_port, network_metrics_port) = if node.cluster.is_empty() {
+            port_allocator.allocate(&node.domain)
         } else {
-            0
+            (50000, 60000)
         };
-        let network_port = 50000 + offset;
-        let network_metrics_port = 60000 + offset;
+        let chaincode_port = (node.cluster.is_empty() && is_chaincode)
+            .then(|| port_allocator.allocate_port(&node.domain, DEFAULT_CHAINCODE_PORT));
         let domain = node.domain.to_string();
         let node_account = node_accounts[i].clone();
 
@@ -367,6 +391,15 @@ pub fn execute_create(opts: CreateOpts) -> Result<(), Error> {
             log_level: opts.log_level.clone(),
             log_file_path: opts.log_file_path.clone(),
             jaeger_agent_endpoint: opts.jaeger_agent_endpoint.clone(),
+            service_log_level: vec![],
+            service_log_filter: vec![],
+            service_log_file_path: vec![],
+            service_jaeger_agent_endpoint: vec![],
+            tracing_exporter: "otlp-grpc".to_string(),
+            tracing_endpoint: None,
+            tracing_sampling_ratio: 1.0,
+            tracing_resource_attributes: "".to_string(),
+            chaincode_port,
             account: node_account,
             network_metrics_port,
             consensus_metrics_port: network_metrics_port + 1,
@@ -392,6 +425,7 @@ pub fn execute_create(opts: CreateOpts) -> Result<(), Error> {
             config_dir: opts.config_dir.clone(),
             domain: domain.clone(),
             config_name: "config.toml".to_string(),
+            force: false,
         })
         .unwrap();
     }
@@ -419,6 +453,7 @@ pub struct AppendOpts {
     /// node network address looks like localhost:40002:node2:k8s_cluster_name:namespace
     /// k8s_cluster_name is optional, none means not k8s env.
     /// namespace is optional, none means default namespace.
+    /// protocol prefix is optional, quic/tls/tcp, none means quic, like tcp/localhost:40000:node0
     #[clap(long = "node")]
     pub node: String,
     /// is chain in danger mode
@@ -430,30 +465,39 @@ pub struct AppendOpts {
     /// disable metrics
     #[clap(long = "disable-metrics")]
     pub disable_metrics: bool,
-    /// cloud_storage.access_key_id
+    /// storage_backend.access_key_id, account_name of azblob, env:NAME, file:PATH or secret:NAME/KEY refers to credential instead of plain text
     #[clap(long = "access-key-id", default_value = "")]
     pub access_key_id: String,
-    /// cloud_storage.secret_access_key
+    /// storage_backend.secret_access_key, account_key of azblob, env:NAME, file:PATH or secret:NAME/KEY refers to credential instead of plain text
     #[clap(long = "secret-access-key", default_value = "")]
     pub secret_access_key: String,
-    /// cloud_storage.endpoint
+    /// storage_backend.endpoint
     #[clap(long = "s3-endpoint", default_value = "")]
     pub s3_endpoint: String,
-    /// cloud_storage.bucket
+    /// storage_backend.bucket, container of azblob
     #[clap(long = "s3-bucket", default_value = "")]
     pub s3_bucket: String,
-    /// cloud_storage.service_type: s3/oss(aliyun)/obs(huawei)/cos(tencent)/azblob(azure)
+    /// storage_backend.type: fs/s3/oss(aliyun)/obs(huawei)/cos(tencent)/azblob(azure), empty means no cloud storage
     #[clap(long = "service-type", default_value = "")]
     pub service_type: String,
-    /// cloud_storage.root
+    /// storage_backend.root
     #[clap(long = "s3-root", default_value = "")]
     pub s3_root: String,
-    /// cloud_storage.region
+    /// storage_backend.region
     #[clap(long = "s3-region", default_value = "")]
     pub s3_region: String,
     /// exporter.base_path
     #[clap(long = "exporter-path", default_value = "")]
     pub exporter_path: String,
+    /// first port of grpc ports allocated for local nodes
+    #[clap(long = "grpc-port-base", default_value = "50000")]
+    pub grpc_port_base: u16,
+    /// first port of metrics ports allocated for local nodes
+    #[clap(long = "metrics-port-base", default_value = "60000")]
+    pub metrics_port_base: u16,
+    /// skip ports which are bound on this machine when allocate ports
+    #[clap(long = "check-port-bind")]
+    pub check_port_bind: bool,
 }
 
 /// append a new node into chain
@@ -463,6 +507,10 @@ pub fn execute_append(opts: AppendOpts) -> Result<(), Error> {
         &opts.config_dir, &opts.chain_name, CHAIN_CONFIG_FILE
     );
     let chain_config = read_chain_config(file_name).unwrap();
+    let is_chaincode = find_micro_service(&chain_config, EXECUTOR_CHAINCODE);
+
+    // parse node network info
+    let new_node = parse_node(&opts.node)?;
 
     // create account for new node
     let (addr, _) = execute_new_account(NewAccountOpts {
@@ -471,33 +519,6 @@ pub fn execute_append(opts: AppendOpts) -> Result<(), Error> {
     })
     .unwrap();
 
-    // parse node network info
-    let node_network_info: Vec<&str> = opts.node.split(':').collect();
-    let new_node = if node_network_info.len() == 3 {
-        NodeNetworkAddressBuilder::default()
-            .host(node_network_info[0].to_string())
-            .port(node_network_info[1].parse::<u16>().unwrap())
-            .domain(node_network_info[2].to_string())
-            .build()
-    } else if node_network_info.len() == 4 {
-        NodeNetworkAddressBuilder::default()
-            .host(node_network_info[0].to_string())
-            .port(node_network_info[1].parse::<u16>().unwrap())
-            .domain(node_network_info[2].to_string())
-            .cluster(node_network_info[3].to_string())
-            .build()
-    } else if node_network_info.len() == 5 {
-        NodeNetworkAddressBuilder::default()
-            .host(node_network_info[0].to_string())
-            .port(node_network_info[1].parse::<u16>().unwrap())
-            .domain(node_network_info[2].to_string())
-            .cluster(node_network_info[3].to_string())
-            .name_space(node_network_info[4].to_string())
-            .build()
-    } else {
-        panic!("invalid node network address format!")
-    };
-
     // append node
     execute_append_node(AppendNodeOpts {
         chain_name: opts.chain_name.clone(),
@@ -540,20 +561,27 @@ pub fn execute_append(opts: AppendOpts) -> Result<(), Error> {
             config_dir: opts.config_dir.clone(),
             domain: domain.clone(),
             config_name: "config.toml".to_string(),
+            force: false,
         })
         .unwrap();
     }
 
     // new node need init and update
-    // for k8s node offset is 0
-    // for none k8s node offset used to avoid port conflict
-    let offset = if new_node.cluster.is_empty() {
-        (new_node.port - 40000) * 100
+    let (network_port, network_metrics_port, chaincode_port) = if new_node.cluster.is_empty() {
+        let mut port_allocator = LocalPortAllocator::new(
+            &opts.config_dir,
+            &opts.chain_name,
+            opts.grpc_port_base,
+            opts.metrics_port_base,
+            opts.check_port_bind,
+        );
+        let (network_port, network_metrics_port) = port_allocator.allocate(&new_node.domain);
+        let chaincode_port = is_chaincode
+            .then(|| port_allocator.allocate_port(&new_node.domain, DEFAULT_CHAINCODE_PORT));
+        (network_port, network_metrics_port, chaincode_port)
     } else {
-        0
+        (50000, 60000, None)
     };
-    let network_port = 50000 + offset;
-    let network_metrics_port = 60000 + offset;
     let domain = new_node.domain;
 
     execute_init_node(InitNodeOpts {
@@ -568,6 +596,15 @@ pub fn execute_append(opts: AppendOpts) -> Result<(), Error> {
         log_level: opts.log_level,
         log_file_path: opts.log_file_path,
         jaeger_agent_endpoint: opts.jaeger_agent_endpoint,
+        service_log_level: vec![],
+        service_log_filter: vec![],
+        service_log_file_path: vec![],
+        service_jaeger_agent_endpoint: vec![],
+        tracing_exporter: "otlp-grpc".to_string(),
+        tracing_endpoint: None,
+        tracing_sampling_ratio: 1.0,
+        tracing_resource_attributes: "".to_string(),
+        chaincode_port,
         account: addr,
         network_metrics_port,
         consensus_metrics_port: network_metrics_port + 1,
@@ -593,6 +630,7 @@ pub fn execute_append(opts: AppendOpts) -> Result<(), Error> {
         config_dir: opts.config_dir.clone(),
         domain,
         config_name: "config.toml".to_string(),
+        force: false,
     })
     .unwrap();
 
@@ -648,6 +686,7 @@ pub fn execute_delete(opts: DeleteOpts) -> Result<(), Error> {
             config_dir: opts.config_dir.clone(),
             domain: domain.clone(),
             config_name: "config.toml".to_string(),
+            force: false,
         })
         .unwrap();
     }
@@ -685,10 +724,12 @@ mod cmd_test {
             storage_tag: "latest".to_string(),
             controller_image: "controller_hsm".to_string(),
             controller_tag: "latest".to_string(),
+            internal_port: 40000,
             admin: "a81a6d5ebf5bb612dd52b37f743d2eb7a90807f7".to_string(),
             node_list: "localhost:40000:node0:k8s,localh
             ost:40001:node1:k8s,localhost:40002:node2:k8s:cita,rivtower.com:40003:node3,192.168.160.20:40004:node4"
                 .to_string(),
+            node_list_file: None,
             log_level: "info".to_string(),
             log_file_path: None,
             jaeger_agent_endpoint: None,
@@ -703,6 +744,9 @@ mod cmd_test {
             s3_root: "".to_string(),
             s3_region: "".to_string(),
             exporter_path: "".to_string(),
+            grpc_port_base: 50000,
+            metrics_port_base: 60000,
+            check_port_bind: false,
         })
         .unwrap();
 
@@ -727,8 +771,10 @@ mod cmd_test {
             storage_tag: "latest".to_string(),
             controller_image: "controller_hsm".to_string(),
             controller_tag: "latest".to_string(),
+            internal_port: 40000,
             admin: "a81a6d5ebf5bb612dd52b37f743d2eb7a90807f7".to_string(),
             node_list: "localhost:40000:node0:k8s,localhost:40001:node1:k8s".to_string(),
+            node_list_file: None,
             log_level: "info".to_string(),
             log_file_path: None,
             jaeger_agent_endpoint: None,
@@ -743,6 +789,9 @@ mod cmd_test {
             s3_root: "".to_string(),
             s3_region: "".to_string(),
             exporter_path: "".to_string(),
+            grpc_port_base: 50000,
+            metrics_port_base: 60000,
+            check_port_bind: false,
         })
         .unwrap();
 
@@ -764,6 +813,9 @@ mod cmd_test {
             s3_root: "".to_string(),
             s3_region: "".to_string(),
             exporter_path: "".to_string(),
+            grpc_port_base: 50000,
+            metrics_port_base: 60000,
+            check_port_bind: false,
         })
         .unwrap();
 
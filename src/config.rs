@@ -16,6 +16,7 @@ pub mod chain_config;
 pub mod consensus_overlord;
 pub mod consensus_raft;
 pub mod controller;
+pub mod executor_chaincode;
 pub mod executor_evm;
 pub mod log_config;
 pub mod network_zenoh;
@@ -12,19 +12,120 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::config::consensus_overlord::OverlordParams;
 use crate::config::controller::{
     GenesisBlock, GenesisBlockBuilder, SystemConfigBuilder, SystemConfigFile,
 };
+use crate::constant::{
+    CONSENSUS, CONTROLLER, DEFAULT_INTERNAL_PORT, EXECUTOR, NETWORK, SCHEMA_VERSION, STORAGE,
+};
+use crate::error::Error;
+use crate::registry::{MicroServiceRegistry, SERVICES};
+use crate::util::{crypto_hash, sm3_hash};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
 use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+/// transport of network_zenoh
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum NetworkProtocol {
+    #[default]
+    Quic,
+    Tls,
+    Tcp,
+}
+
+impl NetworkProtocol {
+    /// protocol used by k8s Service and EndpointSlice
+    pub fn k8s_protocol(&self) -> &'static str {
+        match self {
+            NetworkProtocol::Quic => "UDP",
+            NetworkProtocol::Tls | NetworkProtocol::Tcp => "TCP",
+        }
+    }
+}
+
+impl fmt::Display for NetworkProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetworkProtocol::Quic => write!(f, "quic"),
+            NetworkProtocol::Tls => write!(f, "tls"),
+            NetworkProtocol::Tcp => write!(f, "tcp"),
+        }
+    }
+}
+
+impl FromStr for NetworkProtocol {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "quic" => Ok(NetworkProtocol::Quic),
+            "tls" => Ok(NetworkProtocol::Tls),
+            "tcp" => Ok(NetworkProtocol::Tcp),
+            _ => Err(format!(
+                "invalid network protocol {s}: should be quic/tls/tcp"
+            )),
+        }
+    }
+}
+
+/// observer syncs blocks and serves rpc, but never be a validator
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeRole {
+    #[default]
+    Validator,
+    Observer,
+}
+
+impl fmt::Display for NodeRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NodeRole::Validator => write!(f, "validator"),
+            NodeRole::Observer => write!(f, "observer"),
+        }
+    }
+}
+
+impl FromStr for NodeRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "validator" => Ok(NodeRole::Validator),
+            "observer" => Ok(NodeRole::Observer),
+            _ => Err(format!(
+                "invalid node role {s}: should be validator/observer"
+            )),
+        }
+    }
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone, Eq)]
 pub struct NodeNetworkAddress {
     pub host: String,
     pub port: u16,
     pub domain: String,
+    #[serde(default)]
     pub cluster: String,
+    #[serde(default = "default_name_space")]
     pub name_space: String,
+    #[serde(default)]
+    pub protocol: NetworkProtocol,
+    // listen port inside k8s, none means use internal_port of chain
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub internal_port: Option<u16>,
+    #[serde(default)]
+    pub role: NodeRole,
+}
+
+fn default_name_space() -> String {
+    "default".to_string()
 }
 
 impl PartialEq for NodeNetworkAddress {
@@ -41,12 +142,163 @@ impl Hash for NodeNetworkAddress {
     }
 }
 
+/// node network address looks like:
+/// [protocol/]host:port:domain[:cluster[:namespace]]
+/// [protocol/][ipv6]:port:domain[:cluster[:namespace]]
+/// host=..;port=..;domain=..[;cluster=..][;namespace=..][;protocol=..][;internal_port=..][;role=..]
+/// protocol is optional, default is quic
+/// role is optional, default is validator
+impl FromStr for NodeNetworkAddress {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let mut builder = NodeNetworkAddressBuilder::default();
+
+        if s.contains('=') {
+            let mut has_host = false;
+            let mut has_port = false;
+            for kv in s.split(';').filter(|kv| !kv.trim().is_empty()) {
+                let (key, value) = kv
+                    .split_once('=')
+                    .ok_or(format!("invalid node network address {s}: bad item {kv}"))?;
+                let value = value.trim().to_string();
+                match key.trim() {
+                    "host" => {
+                        builder.host(value.trim_matches(|c| c == '[' || c == ']').to_string());
+                        has_host = true;
+                    }
+                    "port" => {
+                        builder.port(parse_port(s, &value)?);
+                        has_port = true;
+                    }
+                    "domain" => {
+                        builder.domain(value);
+                    }
+                    "cluster" => {
+                        builder.cluster(value);
+                    }
+                    "namespace" | "name_space" => {
+                        builder.name_space(value);
+                    }
+                    "protocol" => {
+                        builder.protocol(value.parse()?);
+                    }
+                    "internal_port" => {
+                        builder.internal_port(parse_port(s, &value)?);
+                    }
+                    "role" => {
+                        builder.role(value.parse()?);
+                    }
+                    key => {
+                        return Err(format!(
+                            "invalid node network address {s}: unknown key {key}"
+                        ))
+                    }
+                }
+            }
+            if !has_host || !has_port || builder.domain.is_empty() {
+                return Err(format!(
+                    "invalid node network address {s}: host, port and domain are required"
+                ));
+            }
+            return Ok(builder.build());
+        }
+
+        let address = match s.split_once('/') {
+            Some((protocol, address)) => {
+                builder.protocol(protocol.parse()?);
+                address
+            }
+            None => s,
+        };
+
+        // ipv6 host must be bracketed because it contains ':'
+        let rest = if let Some(bracketed) = address.strip_prefix('[') {
+            let (host, rest) = bracketed
+                .split_once(']')
+                .ok_or(format!("invalid node network address {s}: missing ']'"))?;
+            builder.host(host.to_string());
+            rest.strip_prefix(':')
+                .ok_or(format!("invalid node network address {s}: missing port"))?
+        } else {
+            let (host, rest) = address
+                .split_once(':')
+                .ok_or(format!("invalid node network address {s}: missing port"))?;
+            builder.host(host.to_string());
+            rest
+        };
+
+        let node_network_info: Vec<&str> = rest.split(':').collect();
+        if node_network_info.len() < 2 || node_network_info.len() > 4 {
+            return Err(format!(
+                "invalid node network address {s}: should be host:port:domain[:cluster[:namespace]]"
+            ));
+        }
+        builder
+            .port(parse_port(s, node_network_info[0])?)
+            .domain(node_network_info[1].to_string());
+        if node_network_info.len() > 2 {
+            builder.cluster(node_network_info[2].to_string());
+        }
+        if node_network_info.len() > 3 {
+            builder.name_space(node_network_info[3].to_string());
+        }
+        Ok(builder.build())
+    }
+}
+
+fn parse_port(s: &str, port: &str) -> Result<u16, String> {
+    port.parse::<u16>()
+        .map_err(|_| format!("invalid node network address {s}: bad port {port}"))
+}
+
+/// domain become part of k8s service name, so it must be a DNS-1123 label
+pub fn check_domain(domain: &str) -> Result<(), String> {
+    let is_valid = !domain.is_empty()
+        && domain.len() <= 63
+        && domain
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        && !domain.starts_with('-')
+        && !domain.ends_with('-');
+    if is_valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "invalid domain {domain}: must consist of lower case alphanumeric characters or '-', start and end with an alphanumeric character, and at most 63 characters"
+        ))
+    }
+}
+
+/// check domain names, duplicate domains and duplicate host:port
+pub fn check_node_list(node_list: &[NodeNetworkAddress]) -> Result<(), String> {
+    let mut domains = HashSet::new();
+    let mut endpoints = HashSet::new();
+    for node in node_list {
+        check_domain(&node.domain)?;
+        if !domains.insert(&node.domain) {
+            return Err(format!("duplicate domain {}", &node.domain));
+        }
+        if !endpoints.insert((&node.host, node.port)) {
+            return Err(format!(
+                "duplicate host and port {}:{}",
+                &node.host, node.port
+            ));
+        }
+    }
+    Ok(())
+}
+
 pub struct NodeNetworkAddressBuilder {
     pub host: String,
     pub port: u16,
     pub domain: String,
     pub cluster: String,
     pub name_space: String,
+    pub protocol: NetworkProtocol,
+    pub internal_port: Option<u16>,
+    pub role: NodeRole,
 }
 
 impl Default for NodeNetworkAddressBuilder {
@@ -57,6 +309,9 @@ impl Default for NodeNetworkAddressBuilder {
             domain: "".to_string(),
             cluster: "".to_string(),
             name_space: "default".to_string(),
+            protocol: NetworkProtocol::default(),
+            internal_port: None,
+            role: NodeRole::default(),
         }
     }
 }
@@ -82,12 +337,26 @@ impl NodeNetworkAddressBuilder {
         self
     }
 
-    #[allow(dead_code)]
     pub fn name_space(&mut self, name_space: String) -> &mut NodeNetworkAddressBuilder {
         self.name_space = name_space;
         self
     }
 
+    pub fn protocol(&mut self, protocol: NetworkProtocol) -> &mut NodeNetworkAddressBuilder {
+        self.protocol = protocol;
+        self
+    }
+
+    pub fn internal_port(&mut self, internal_port: u16) -> &mut NodeNetworkAddressBuilder {
+        self.internal_port = Some(internal_port);
+        self
+    }
+
+    pub fn role(&mut self, role: NodeRole) -> &mut NodeNetworkAddressBuilder {
+        self.role = role;
+        self
+    }
+
     pub fn build(&self) -> NodeNetworkAddress {
         NodeNetworkAddress {
             host: self.host.clone(),
@@ -95,6 +364,55 @@ impl NodeNetworkAddressBuilder {
             domain: self.domain.clone(),
             cluster: self.cluster.clone(),
             name_space: self.name_space.clone(),
+            protocol: self.protocol,
+            internal_port: self.internal_port,
+            role: self.role,
+        }
+    }
+}
+
+/// role of micro service in node
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ServiceRole {
+    Network,
+    Consensus,
+    Executor,
+    Storage,
+    Controller,
+}
+
+impl ServiceRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ServiceRole::Network => NETWORK,
+            ServiceRole::Consensus => CONSENSUS,
+            ServiceRole::Executor => EXECUTOR,
+            ServiceRole::Storage => STORAGE,
+            ServiceRole::Controller => CONTROLLER,
+        }
+    }
+}
+
+impl fmt::Display for ServiceRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for ServiceRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            NETWORK => Ok(ServiceRole::Network),
+            CONSENSUS => Ok(ServiceRole::Consensus),
+            EXECUTOR => Ok(ServiceRole::Executor),
+            STORAGE => Ok(ServiceRole::Storage),
+            CONTROLLER => Ok(ServiceRole::Controller),
+            _ => Err(format!(
+                "invalid service role {s}: should be network/consensus/executor/storage/controller"
+            )),
         }
     }
 }
@@ -103,11 +421,53 @@ impl NodeNetworkAddressBuilder {
 pub struct MicroService {
     pub image: String,
     pub tag: String,
+    // missing in chain_config of old version, filled by image when loaded
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<ServiceRole>,
+    // implementation in MicroServiceRegistry, default is image
+    // set it when image is mirrored or custom built with another name
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    // pin image by digest, like sha256:...
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+    // docker registry and repo of this micro service, default is set by update-yaml
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registry: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+}
+
+impl MicroService {
+    pub fn kind(&self) -> &str {
+        self.kind.as_deref().unwrap_or(&self.image)
+    }
+
+    /// full image reference used in k8s
+    pub fn image_ref(&self, docker_registry: &str, docker_repo: &str) -> String {
+        let image_ref = format!(
+            "{}/{}/{}:{}",
+            self.registry.as_deref().unwrap_or(docker_registry),
+            self.repo.as_deref().unwrap_or(docker_repo),
+            &self.image,
+            &self.tag
+        );
+        match &self.digest {
+            Some(digest) => format!("{image_ref}@{digest}"),
+            None => image_ref,
+        }
+    }
 }
 
 pub struct MicroServiceBuilder {
     pub image: String,
     pub tag: String,
+    pub role: Option<ServiceRole>,
 }
 
 impl Default for MicroServiceBuilder {
@@ -115,6 +475,7 @@ impl Default for MicroServiceBuilder {
         MicroServiceBuilder {
             image: "".to_string(),
             tag: "latest".to_string(),
+            role: None,
         }
     }
 }
@@ -130,10 +491,63 @@ impl MicroServiceBuilder {
         self
     }
 
+    pub fn role(&mut self, role: ServiceRole) -> &mut MicroServiceBuilder {
+        self.role = Some(role);
+        self
+    }
+
     pub fn build(&self) -> MicroService {
         MicroService {
             image: self.image.clone(),
             tag: self.tag.clone(),
+            role: self.role,
+            kind: None,
+            digest: None,
+            registry: None,
+            repo: None,
+        }
+    }
+}
+
+/// how nodes connect to each other in network
+#[derive(Debug, Serialize, Clone, Deserialize, Default, Eq, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Topology {
+    /// every node connects to all other nodes
+    #[default]
+    FullMesh,
+    /// peers of each node are listed by domain, connection is bidirectional
+    Explicit {
+        peers: BTreeMap<String, Vec<String>>,
+    },
+    /// nodes form a ring in order of node list, each node connects to k nearest nodes on both sides
+    Ring { k: usize },
+    /// hubs connect to all nodes, other nodes only connect to hubs
+    HubAndSpoke { hubs: Vec<String> },
+}
+
+impl Topology {
+    pub fn is_full_mesh(&self) -> bool {
+        *self == Topology::FullMesh
+    }
+
+    /// whether node i and node j of node list connected
+    fn is_connected(&self, nodes: &[NodeNetworkAddress], i: usize, j: usize) -> bool {
+        if i == j {
+            return false;
+        }
+        let (a, b) = (&nodes[i].domain, &nodes[j].domain);
+        match self {
+            Topology::FullMesh => true,
+            Topology::Explicit { peers } => {
+                peers.get(a).is_some_and(|p| p.contains(b))
+                    || peers.get(b).is_some_and(|p| p.contains(a))
+            }
+            Topology::Ring { k } => {
+                let distance = i.abs_diff(j);
+                distance.min(nodes.len() - distance) <= *k
+            }
+            Topology::HubAndSpoke { hubs } => hubs.contains(a) || hubs.contains(b),
         }
     }
 }
@@ -147,11 +561,72 @@ pub enum ConfigStage {
 
 #[derive(Debug, Serialize, Clone, Deserialize)]
 pub struct ChainConfig {
+    // missing in chain_config of old version, upgraded by migrations when loaded
+    #[serde(default)]
+    pub schema_version: u64,
     pub system_config: SystemConfigFile,
     pub genesis_block: GenesisBlock,
     pub node_network_address_list: Vec<NodeNetworkAddress>,
     pub micro_service_list: Vec<MicroService>,
     pub stage: ConfigStage,
+    // approvals of validators required by finalize, none means majority of validators
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub approval_quorum: Option<usize>,
+    // validators changed by admin after finalize, genesis validators keep in system_config
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub runtime_validators: Vec<String>,
+    // validators proposed by admin, take effect after the transaction confirmed
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pending_validators: Option<PendingValidators>,
+    // hash of last confirmed transaction which changed validators
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validators_tx_hash: Option<String>,
+    // fingerprint of genesis_block and system_config, computed when finalize
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub genesis_hash: Option<String>,
+    // network listen port of nodes in k8s
+    #[serde(default = "default_internal_port")]
+    pub internal_port: u16,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Topology::is_full_mesh")]
+    pub topology: Topology,
+    // params of consensus_overlord shared by all validators, tunable in any stage so not approved
+    #[serde(default)]
+    #[serde(skip_serializing_if = "OverlordParams::is_default")]
+    pub overlord: OverlordParams,
+}
+
+#[derive(Debug, Serialize, Clone, Deserialize, Eq, PartialEq)]
+pub struct PendingValidators {
+    pub validators: Vec<String>,
+    pub tx_hash: String,
+}
+
+fn default_internal_port() -> u16 {
+    DEFAULT_INTERNAL_PORT
+}
+
+#[derive(Serialize)]
+struct GenesisFingerprint<'a> {
+    genesis_block: &'a GenesisBlock,
+    system_config: &'a SystemConfigFile,
+}
+
+// content of chain config which participants agree on, stage and overlord are excluded
+#[derive(Serialize)]
+struct ApprovalContent<'a> {
+    system_config: &'a SystemConfigFile,
+    genesis_block: &'a GenesisBlock,
+    node_network_address_list: &'a Vec<NodeNetworkAddress>,
+    micro_service_list: &'a Vec<MicroService>,
+    internal_port: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    approval_quorum: Option<usize>,
 }
 
 impl ChainConfig {
@@ -163,6 +638,12 @@ impl ChainConfig {
         self.system_config.set_validators(validators);
     }
 
+    /// approvals of validators required by finalize
+    pub fn required_quorum(&self) -> usize {
+        self.approval_quorum
+            .unwrap_or(self.system_config.validators.len() / 2 + 1)
+    }
+
     pub fn set_node_network_address_list(&mut self, node_list: Vec<NodeNetworkAddress>) {
         self.node_network_address_list = node_list;
     }
@@ -170,6 +651,128 @@ impl ChainConfig {
     pub fn set_stage(&mut self, stage: ConfigStage) {
         self.stage = stage;
     }
+
+    pub fn set_pending_validators(&mut self, validators: Vec<String>, tx_hash: String) {
+        self.pending_validators = Some(PendingValidators {
+            validators,
+            tx_hash,
+        });
+    }
+
+    /// pending validators take effect when their transaction confirmed
+    pub fn confirm_pending_validators(&mut self, tx_hash: &str) -> Result<(), Error> {
+        match self.pending_validators.take() {
+            Some(pending) if pending.tx_hash == tx_hash => {
+                self.runtime_validators = pending.validators;
+                self.validators_tx_hash = Some(pending.tx_hash);
+                Ok(())
+            }
+            pending => {
+                self.pending_validators = pending;
+                Err(Error::ValidatorsNotProposed)
+            }
+        }
+    }
+
+    /// sm3 hash over canonical genesis_block and system_config
+    pub fn compute_genesis_hash(&self) -> String {
+        let fingerprint = GenesisFingerprint {
+            genesis_block: &self.genesis_block,
+            system_config: &self.system_config,
+        };
+        let canonical = toml::to_string(&fingerprint).unwrap();
+        hex::encode(sm3_hash(canonical.as_bytes()))
+    }
+
+    /// genesis fields can't be modified after finalize
+    pub fn check_genesis_hash(&self) -> Result<(), Error> {
+        match &self.genesis_hash {
+            Some(genesis_hash) if *genesis_hash != self.compute_genesis_hash() => {
+                Err(Error::GenesisHashMismatch)
+            }
+            Some(_) => Ok(()),
+            None if self.stage == ConfigStage::Finalize => {
+                println!("genesis_hash is missing in finalized chain_config");
+                Err(Error::GenesisHashMissing)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// hash over canonical chain config which signed by participants
+    pub fn approval_hash(&self) -> Vec<u8> {
+        let content = ApprovalContent {
+            system_config: &self.system_config,
+            genesis_block: &self.genesis_block,
+            node_network_address_list: &self.node_network_address_list,
+            micro_service_list: &self.micro_service_list,
+            internal_port: self.internal_port,
+            approval_quorum: self.approval_quorum,
+        };
+        let canonical = toml::to_string(&content).unwrap();
+        crypto_hash(canonical.as_bytes())
+    }
+
+    /// fill role of micro services from chain_config of old version
+    /// by registered kind, or by prefix of image like before
+    pub fn fill_micro_service_roles(&mut self) {
+        let registry = MicroServiceRegistry::default();
+        for micro_service in &mut self.micro_service_list {
+            if micro_service.role.is_none() {
+                micro_service.role = registry
+                    .get(micro_service.kind())
+                    .map(|kind| kind.service())
+                    .or_else(|| {
+                        SERVICES
+                            .into_iter()
+                            .find(|service| micro_service.image.starts_with(service))
+                    })
+                    .and_then(|service| service.parse().ok());
+            }
+        }
+    }
+
+    /// network listen port of node in k8s
+    pub fn internal_port(&self, node: &NodeNetworkAddress) -> u16 {
+        node.internal_port.unwrap_or(self.internal_port)
+    }
+
+    /// peers of node decided by topology, in order of node list
+    pub fn peers(&self, domain: &str) -> Vec<&NodeNetworkAddress> {
+        let nodes = &self.node_network_address_list;
+        match nodes.iter().position(|n| n.domain == domain) {
+            Some(index) => nodes
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| self.topology.is_connected(nodes, index, *i))
+                .map(|(_, n)| n)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// connections between nodes, each connection only once
+    pub fn topology_edges(&self) -> Vec<(&str, &str)> {
+        let nodes = &self.node_network_address_list;
+        let mut edges = Vec::new();
+        for i in 0..nodes.len() {
+            for j in i + 1..nodes.len() {
+                if self.topology.is_connected(nodes, i, j) {
+                    edges.push((nodes[i].domain.as_str(), nodes[j].domain.as_str()));
+                }
+            }
+        }
+        edges
+    }
+
+    /// validators in effect on the running chain
+    pub fn current_validators(&self) -> &Vec<String> {
+        if self.runtime_validators.is_empty() {
+            &self.system_config.validators
+        } else {
+            &self.runtime_validators
+        }
+    }
 }
 
 pub struct ChainConfigBuilder {
@@ -178,6 +781,8 @@ pub struct ChainConfigBuilder {
     pub node_network_address_list: Vec<NodeNetworkAddress>,
     pub micro_service_list: Vec<MicroService>,
     pub stage: ConfigStage,
+    pub internal_port: u16,
+    pub approval_quorum: Option<usize>,
 }
 
 impl Default for ChainConfigBuilder {
@@ -188,6 +793,8 @@ impl Default for ChainConfigBuilder {
             node_network_address_list: Vec::new(),
             micro_service_list: Vec::new(),
             stage: ConfigStage::Init,
+            internal_port: DEFAULT_INTERNAL_PORT,
+            approval_quorum: None,
         }
     }
 }
@@ -203,15 +810,6 @@ impl ChainConfigBuilder {
         self
     }
 
-    #[allow(dead_code)]
-    pub fn node_network_address_list(
-        &mut self,
-        node_network_address_list: Vec<NodeNetworkAddress>,
-    ) -> &mut ChainConfigBuilder {
-        self.node_network_address_list = node_network_address_list;
-        self
-    }
-
     pub fn micro_service_list(
         &mut self,
         micro_service_list: Vec<MicroService>,
@@ -220,13 +818,201 @@ impl ChainConfigBuilder {
         self
     }
 
+    pub fn internal_port(&mut self, internal_port: u16) -> &mut ChainConfigBuilder {
+        self.internal_port = internal_port;
+        self
+    }
+
+    pub fn approval_quorum(&mut self, approval_quorum: Option<usize>) -> &mut ChainConfigBuilder {
+        self.approval_quorum = approval_quorum;
+        self
+    }
+
     pub fn build(&self) -> ChainConfig {
         ChainConfig {
+            schema_version: SCHEMA_VERSION,
             system_config: self.system_config.clone(),
             genesis_block: self.genesis_block.clone(),
             node_network_address_list: self.node_network_address_list.clone(),
             micro_service_list: self.micro_service_list.clone(),
             stage: self.stage.clone(),
+            approval_quorum: self.approval_quorum,
+            runtime_validators: Vec::new(),
+            pending_validators: None,
+            validators_tx_hash: None,
+            genesis_hash: None,
+            internal_port: self.internal_port,
+            topology: Topology::default(),
+            overlord: OverlordParams::default(),
         }
     }
 }
+
+#[cfg(test)]
+mod chain_config_test {
+    use super::*;
+
+    #[test]
+    fn parse_node_test() {
+        let node = "localhost:40000:node0"
+            .parse::<NodeNetworkAddress>()
+            .unwrap();
+        assert_eq!(node.host, "localhost");
+        assert_eq!(node.port, 40000);
+        assert_eq!(node.name_space, "default");
+
+        let node = "[::1]:40001:node1:k8s:cita"
+            .parse::<NodeNetworkAddress>()
+            .unwrap();
+        assert_eq!(node.host, "::1");
+        assert_eq!(node.cluster, "k8s");
+        assert_eq!(node.name_space, "cita");
+
+        let node = "host=fe80::1;port=40002;domain=node2;cluster=k8s"
+            .parse::<NodeNetworkAddress>()
+            .unwrap();
+        assert_eq!(node.host, "fe80::1");
+        assert_eq!(node.domain, "node2");
+
+        assert!("localhost:4000x:node0"
+            .parse::<NodeNetworkAddress>()
+            .is_err());
+        assert!("::1:40000:node0".parse::<NodeNetworkAddress>().is_err());
+        assert!("host=localhost;domain=node0"
+            .parse::<NodeNetworkAddress>()
+            .is_err());
+    }
+
+    #[test]
+    fn topology_test() {
+        let mut chain_config = ChainConfigBuilder::default().build();
+        chain_config.set_node_network_address_list(
+            (0..5)
+                .map(|i| {
+                    NodeNetworkAddressBuilder::default()
+                        .port(40000 + i)
+                        .domain(format!("node{i}"))
+                        .build()
+                })
+                .collect(),
+        );
+        assert_eq!(chain_config.topology_edges().len(), 10);
+
+        chain_config.topology = Topology::Ring { k: 1 };
+        let peers: Vec<&str> = chain_config
+            .peers("node0")
+            .iter()
+            .map(|n| n.domain.as_str())
+            .collect();
+        assert_eq!(peers, vec!["node1", "node4"]);
+        assert_eq!(chain_config.topology_edges().len(), 5);
+
+        chain_config.topology = Topology::HubAndSpoke {
+            hubs: vec!["node0".to_string()],
+        };
+        assert_eq!(chain_config.peers("node1").len(), 1);
+        assert_eq!(chain_config.peers("node0").len(), 4);
+
+        let mut peers = BTreeMap::new();
+        peers.insert("node0".to_string(), vec!["node1".to_string()]);
+        chain_config.topology = Topology::Explicit { peers };
+        assert_eq!(chain_config.peers("node1").len(), 1);
+        assert!(chain_config.peers("node2").is_empty());
+    }
+
+    #[test]
+    fn check_node_list_test() {
+        let node0 = "localhost:40000:node0"
+            .parse::<NodeNetworkAddress>()
+            .unwrap();
+        let node1 = "localhost:40001:node1"
+            .parse::<NodeNetworkAddress>()
+            .unwrap();
+        assert!(check_node_list(&[node0.clone(), node1]).is_ok());
+
+        let dup_domain = "localhost:40002:node0"
+            .parse::<NodeNetworkAddress>()
+            .unwrap();
+        assert!(check_node_list(&[node0.clone(), dup_domain]).is_err());
+
+        let dup_endpoint = "localhost:40000:node2"
+            .parse::<NodeNetworkAddress>()
+            .unwrap();
+        assert!(check_node_list(&[node0, dup_endpoint]).is_err());
+
+        assert!(check_domain("Node_0").is_err());
+        assert!(check_domain("-node0").is_err());
+        assert!(check_domain("node-0").is_ok());
+    }
+
+    #[test]
+    fn micro_service_test() {
+        #[derive(Deserialize)]
+        struct MicroServiceList {
+            micro_service_list: Vec<MicroService>,
+        }
+        let list: MicroServiceList = toml::from_str(
+            r#"
+            [[micro_service_list]]
+            image = "network_zenoh"
+            tag = "latest"
+
+            [[micro_service_list]]
+            image = "consensus_mirror"
+            tag = "v6.7.4"
+            kind = "consensus_overlord"
+
+            [[micro_service_list]]
+            image = "executor_custom"
+            tag = "latest"
+            "#,
+        )
+        .unwrap();
+        let mut chain_config = ChainConfigBuilder::default()
+            .micro_service_list(list.micro_service_list)
+            .build();
+        chain_config.fill_micro_service_roles();
+        let roles: Vec<Option<ServiceRole>> = chain_config
+            .micro_service_list
+            .iter()
+            .map(|micro_service| micro_service.role)
+            .collect();
+        assert_eq!(
+            roles,
+            vec![
+                Some(ServiceRole::Network),
+                Some(ServiceRole::Consensus),
+                Some(ServiceRole::Executor)
+            ]
+        );
+
+        let mut micro_service = chain_config.micro_service_list[1].clone();
+        assert_eq!(micro_service.kind(), "consensus_overlord");
+        assert_eq!(
+            micro_service.image_ref("docker.io", "citacloud"),
+            "docker.io/citacloud/consensus_mirror:v6.7.4"
+        );
+        micro_service.registry = Some("registry.local".to_string());
+        micro_service.digest = Some("sha256:1234".to_string());
+        assert_eq!(
+            micro_service.image_ref("docker.io", "citacloud"),
+            "registry.local/citacloud/consensus_mirror:v6.7.4@sha256:1234"
+        );
+    }
+
+    #[test]
+    fn pending_validators_test() {
+        let mut chain_config = ChainConfigBuilder::default().build();
+        chain_config.set_validators(vec!["aa".to_string()]);
+        chain_config.set_pending_validators(vec!["bb".to_string()], "1234".to_string());
+        assert_eq!(chain_config.current_validators(), &vec!["aa".to_string()]);
+
+        assert!(chain_config.confirm_pending_validators("5678").is_err());
+        assert!(chain_config.pending_validators.is_some());
+
+        chain_config.confirm_pending_validators("1234").unwrap();
+        assert_eq!(chain_config.current_validators(), &vec!["bb".to_string()]);
+        assert_eq!(chain_config.validators_tx_hash.as_deref(), Some("1234"));
+        assert!(chain_config.pending_validators.is_none());
+    }
+}
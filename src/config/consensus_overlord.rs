@@ -12,12 +12,66 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::constant::CONSENSUS_OVERLORD;
-use crate::traits::TomlWriter;
+use crate::constant::{CONSENSUS, CONSENSUS_OVERLORD};
+use crate::error::Error;
+use crate::registry::NodeContext;
+use crate::traits::{MicroServiceKind, TomlWriter};
 use serde::{Deserialize, Serialize};
 
 use super::log_config::LogConfig;
 
+/// valid range of ratios, which are in tenths of block interval
+const RATIO_RANGE: std::ops::RangeInclusive<u64> = 1..=100;
+
+/// timing of consensus_overlord, must be the same on all validators so set in chain_config
+/// unset params are omitted so consensus_overlord uses its own defaults
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct OverlordParams {
+    // timeout of propose step, in tenths of block interval
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub propose_ratio: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prevote_ratio: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub precommit_ratio: Option<u64>,
+    // timeout of brake step, in tenths of block interval
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub brake_ratio: Option<u64>,
+    // max timeout of a step in milliseconds, timeouts grow with round up to it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+}
+
+impl OverlordParams {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// ratios must be in [1, 100], timeout_ms must not be less than block interval
+    pub fn check(&self, block_interval: u32) -> Result<(), String> {
+        for (name, ratio) in [
+            ("propose_ratio", self.propose_ratio),
+            ("prevote_ratio", self.prevote_ratio),
+            ("precommit_ratio", self.precommit_ratio),
+            ("brake_ratio", self.brake_ratio),
+        ] {
+            if let Some(ratio) = ratio {
+                if !RATIO_RANGE.contains(&ratio) {
+                    return Err(format!("{name} must be in [1, 100], got {ratio}"));
+                }
+            }
+        }
+        if let Some(timeout_ms) = self.timeout_ms {
+            if timeout_ms < block_interval as u64 * 1000 {
+                return Err(format!(
+                    "timeout_ms must not be less than block interval {block_interval}s, got {timeout_ms}"
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Serialize, Clone, Deserialize)]
 pub struct ConsensusOverlord {
     pub domain: String,
@@ -33,6 +87,9 @@ pub struct ConsensusOverlord {
     pub enable_metrics: bool,
 
     pub log_config: LogConfig,
+
+    #[serde(flatten)]
+    pub params: OverlordParams,
 }
 
 impl ConsensusOverlord {
@@ -53,6 +110,7 @@ impl ConsensusOverlord {
             metrics_port,
             enable_metrics,
             log_config,
+            params: OverlordParams::default(),
         }
     }
 }
@@ -62,3 +120,81 @@ impl TomlWriter for ConsensusOverlord {
         CONSENSUS_OVERLORD.to_string()
     }
 }
+
+pub struct ConsensusOverlordKind;
+
+impl MicroServiceKind for ConsensusOverlordKind {
+    fn image(&self) -> &str {
+        CONSENSUS_OVERLORD
+    }
+
+    fn service(&self) -> &str {
+        CONSENSUS
+    }
+
+    fn section(&self) -> &str {
+        CONSENSUS_OVERLORD
+    }
+
+    fn render(&self, ctx: &NodeContext, config_file: &str) -> Result<(), Error> {
+        let node_config = ctx.node_config;
+        let params = &ctx.chain_config.overlord;
+        if let Err(e) = params.check(ctx.chain_config.system_config.block_interval) {
+            println!("invalid overlord params: {e}");
+            return Err(Error::InvalidConsensusConfig);
+        }
+        let mut consensus_config = ConsensusOverlord::new(
+            ctx.real_domain(),
+            node_config.grpc_ports.controller_port,
+            node_config.grpc_ports.consensus_port,
+            node_config.grpc_ports.network_port,
+            node_config.metrics_ports.consensus_metrics_port,
+            node_config.enable_metrics,
+            ctx.log_config(CONSENSUS),
+        );
+        consensus_config.params = params.clone();
+        consensus_config.write(config_file);
+        Ok(())
+    }
+
+    fn command(&self) -> Vec<String> {
+        vec![
+            "consensus".to_string(),
+            "run".to_string(),
+            "-c".to_string(),
+            "/etc/cita-cloud/config/config.toml".to_string(),
+            "-p".to_string(),
+            "/mnt/private_key".to_string(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod consensus_overlord_test {
+    use super::*;
+
+    #[test]
+    fn params_test() {
+        assert_eq!(
+            toml::to_string(&OverlordParams::default()).unwrap(),
+            "".to_string()
+        );
+
+        let params: OverlordParams = toml::from_str(
+            r#"
+            propose_ratio = 15
+            brake_ratio = 7
+            timeout_ms = 3000
+            "#,
+        )
+        .unwrap();
+        assert!(params.check(3).is_ok());
+        assert!(params.check(5).is_err());
+
+        let params = OverlordParams {
+            prevote_ratio: Some(101),
+            ..params
+        };
+        assert!(params.check(3).is_err());
+    }
+}
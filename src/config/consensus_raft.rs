@@ -12,12 +12,53 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::constant::CONSENSUS_RAFT;
-use crate::traits::TomlWriter;
+use crate::constant::{CONSENSUS, CONSENSUS_RAFT};
+use crate::error::Error;
+use crate::registry::NodeContext;
+use crate::traits::{MicroServiceKind, TomlWriter};
 use serde::{Deserialize, Serialize};
 
+use super::log_config::LogConfig;
+
+/// tuning of consensus_raft set in node_config
+/// unset knobs are omitted so consensus_raft uses its own defaults
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct RaftTuning {
+    // ticks without message from leader before a follower starts election
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub election_tick: Option<usize>,
+    // ticks between heartbeats of leader
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heartbeat_tick: Option<usize>,
+    // number of applied entries since last snapshot to trigger a new one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot_threshold: Option<u64>,
+}
+
+impl RaftTuning {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// ticks must be positive and election_tick must be greater than heartbeat_tick
+    pub fn check(&self) -> bool {
+        if self.election_tick == Some(0)
+            || self.heartbeat_tick == Some(0)
+            || self.snapshot_threshold == Some(0)
+        {
+            return false;
+        }
+        match (self.election_tick, self.heartbeat_tick) {
+            (Some(election_tick), Some(heartbeat_tick)) => election_tick > heartbeat_tick,
+            _ => true,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Clone, Deserialize)]
 pub struct Consensus {
+    pub domain: String,
+
     pub network_port: u16,
 
     pub controller_port: u16,
@@ -30,29 +71,13 @@ pub struct Consensus {
 
     pub enable_metrics: bool,
 
+    // used by consensus_raft of old version
     pub log_level: String,
-}
 
-impl Consensus {
-    pub fn new(
-        network_port: u16,
-        controller_port: u16,
-        node_addr: String,
-        grpc_listen_port: u16,
-        metrics_port: u16,
-        enable_metrics: bool,
-        log_level: String,
-    ) -> Self {
-        Self {
-            network_port,
-            controller_port,
-            node_addr,
-            grpc_listen_port,
-            metrics_port,
-            enable_metrics,
-            log_level,
-        }
-    }
+    pub log_config: LogConfig,
+
+    #[serde(flatten)]
+    pub tuning: RaftTuning,
 }
 
 impl TomlWriter for Consensus {
@@ -60,3 +85,87 @@ impl TomlWriter for Consensus {
         CONSENSUS_RAFT.to_string()
     }
 }
+
+pub struct ConsensusRaftKind;
+
+impl MicroServiceKind for ConsensusRaftKind {
+    fn image(&self) -> &str {
+        CONSENSUS_RAFT
+    }
+
+    fn service(&self) -> &str {
+        CONSENSUS
+    }
+
+    fn section(&self) -> &str {
+        CONSENSUS_RAFT
+    }
+
+    fn render(&self, ctx: &NodeContext, config_file: &str) -> Result<(), Error> {
+        let node_config = ctx.node_config;
+        if !node_config.raft.check() {
+            println!("invalid raft: ticks and snapshot_threshold must be greater than 0, election_tick must be greater than heartbeat_tick");
+            return Err(Error::InvalidConsensusConfig);
+        }
+        let log_config = ctx.log_config(CONSENSUS);
+        let consensus_config = Consensus {
+            domain: ctx.real_domain(),
+            network_port: node_config.grpc_ports.network_port,
+            controller_port: node_config.grpc_ports.controller_port,
+            node_addr: ctx.node_address(),
+            grpc_listen_port: node_config.grpc_ports.consensus_port,
+            metrics_port: node_config.metrics_ports.consensus_metrics_port,
+            enable_metrics: node_config.enable_metrics,
+            log_level: log_config.max_level.clone(),
+            log_config,
+            tuning: node_config.raft.clone(),
+        };
+        consensus_config.write(config_file);
+        Ok(())
+    }
+
+    fn command(&self) -> Vec<String> {
+        vec![
+            "consensus".to_string(),
+            "run".to_string(),
+            "-c".to_string(),
+            "/etc/cita-cloud/config/config.toml".to_string(),
+            "--stdout".to_string(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod consensus_raft_test {
+    use super::*;
+
+    #[test]
+    fn tuning_test() {
+        assert!(RaftTuning::default().check());
+
+        let tuning: RaftTuning = toml::from_str(
+            r#"
+            election_tick = 20
+            heartbeat_tick = 5
+            "#,
+        )
+        .unwrap();
+        assert!(tuning.check());
+        assert_eq!(tuning.snapshot_threshold, None);
+        assert_eq!(
+            toml::to_string(&tuning).unwrap(),
+            "election_tick = 20\nheartbeat_tick = 5\n".to_string()
+        );
+
+        let tuning = RaftTuning {
+            election_tick: Some(5),
+            ..tuning
+        };
+        assert!(!tuning.check());
+        let tuning = RaftTuning {
+            snapshot_threshold: Some(0),
+            ..Default::default()
+        };
+        assert!(!tuning.check());
+    }
+}
@@ -16,7 +16,9 @@ use crate::constant::{
     CONTROLLER, DEFAULT_BLOCK_INTERVAL, DEFAULT_BLOCK_LIMIT, DEFAULT_QUOTA_LIMIT, GENESIS_BLOCK,
     PRE_HASH, SYSTEM_CONFIG,
 };
-use crate::traits::TomlWriter;
+use crate::error::Error;
+use crate::registry::NodeContext;
+use crate::traits::{MicroServiceKind, TomlWriter};
 use crate::util::check_address;
 use serde::{Deserialize, Serialize};
 
@@ -57,6 +59,62 @@ impl TomlWriter for ControllerConfig {
     }
 }
 
+/// controller and controller_hsm share the same config
+pub struct ControllerKind(pub &'static str);
+
+impl MicroServiceKind for ControllerKind {
+    fn image(&self) -> &str {
+        self.0
+    }
+
+    fn service(&self) -> &str {
+        CONTROLLER
+    }
+
+    fn section(&self) -> &str {
+        CONTROLLER
+    }
+
+    fn render(&self, ctx: &NodeContext, config_file: &str) -> Result<(), Error> {
+        let node_config = ctx.node_config;
+        ctx.chain_config.genesis_block.write(config_file);
+        ctx.chain_config.system_config.write(config_file);
+        let controller_config = ControllerConfig {
+            domain: ctx.real_domain(),
+            network_port: node_config.grpc_ports.network_port,
+            consensus_port: node_config.grpc_ports.consensus_port,
+            executor_port: node_config.grpc_ports.executor_port,
+            storage_port: node_config.grpc_ports.storage_port,
+            controller_port: node_config.grpc_ports.controller_port,
+            node_address: ctx.node_address(),
+            validator_address: ctx.validator_address(),
+            metrics_port: node_config.metrics_ports.controller_metrics_port,
+            enable_metrics: node_config.enable_metrics,
+            log_config: ctx.log_config(CONTROLLER),
+            is_danger: node_config.is_danger,
+            tx_persistence: node_config.enable_tx_persistence,
+        };
+        controller_config.write(config_file);
+        Ok(())
+    }
+
+    fn command(&self) -> Vec<String> {
+        vec![
+            "controller".to_string(),
+            "run".to_string(),
+            "-c".to_string(),
+            "/etc/cita-cloud/config/config.toml".to_string(),
+            "-p".to_string(),
+            "/mnt/private_key".to_string(),
+        ]
+    }
+
+    // controller need more time to start
+    fn probe_initial_delay_seconds(&self) -> i32 {
+        60
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemConfigFile {
     pub version: u32,
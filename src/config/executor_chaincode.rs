@@ -0,0 +1,181 @@
+// Copyright Rivtower Technologies LLC.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::constant::{DEFAULT_CHAINCODE_PORT, EXECUTOR, EXECUTOR_CHAINCODE, NODE_CONFIG_FILE};
+use crate::error::Error;
+use crate::registry::NodeContext;
+use crate::traits::{MicroServiceKind, TomlWriter};
+use crate::util::load_node_config;
+use k8s_openapi::api::core::v1::ContainerPort;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::log_config::LogConfig;
+
+/// tuning of executor_chaincode set in node_config
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ChaincodeTuning {
+    // port listened for chaincode to connect, default is 7052
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chaincode_port: Option<u16>,
+    // timeout of executing a transaction by chaincode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execute_timeout_ms: Option<u64>,
+}
+
+impl ChaincodeTuning {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn chaincode_port(&self) -> u16 {
+        self.chaincode_port.unwrap_or(DEFAULT_CHAINCODE_PORT)
+    }
+
+    /// execute_timeout_ms must be positive and chaincode_port must not conflict with ports of executor
+    pub fn check(&self, executor_port: u16, metrics_port: u16) -> bool {
+        let chaincode_port = self.chaincode_port();
+        self.execute_timeout_ms != Some(0)
+            && chaincode_port != 0
+            && chaincode_port != executor_port
+            && chaincode_port != metrics_port
+    }
+}
+
+#[derive(Debug, Serialize, Clone, Deserialize)]
+pub struct ExecutorChaincodeConfig {
+    pub domain: String,
+    pub executor_port: u16,
+    pub metrics_port: u16,
+    pub enable_metrics: bool,
+    pub log_config: LogConfig,
+    pub chaincode_port: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execute_timeout_ms: Option<u64>,
+}
+
+impl TomlWriter for ExecutorChaincodeConfig {
+    fn section(&self) -> String {
+        EXECUTOR_CHAINCODE.to_string()
+    }
+}
+
+pub struct ExecutorChaincodeKind;
+
+impl MicroServiceKind for ExecutorChaincodeKind {
+    fn image(&self) -> &str {
+        EXECUTOR_CHAINCODE
+    }
+
+    fn service(&self) -> &str {
+        EXECUTOR
+    }
+
+    fn section(&self) -> &str {
+        EXECUTOR_CHAINCODE
+    }
+
+    fn render(&self, ctx: &NodeContext, config_file: &str) -> Result<(), Error> {
+        let node_config = ctx.node_config;
+        let executor_port = node_config.grpc_ports.executor_port;
+        let metrics_port = node_config.metrics_ports.executor_metrics_port;
+        if !node_config.chaincode.check(executor_port, metrics_port) {
+            println!("invalid chaincode: execute_timeout_ms must be greater than 0, chaincode_port must be greater than 0 and differ from ports of executor");
+            return Err(Error::InvalidExecutorConfig);
+        }
+        let chaincode_port = node_config.chaincode.chaincode_port();
+        // local nodes of chain share the host, so chaincode ports can't be same
+        if !ctx.is_k8s() {
+            for node in &ctx.chain_config.node_network_address_list {
+                if node.domain == ctx.domain || !node.cluster.is_empty() {
+                    continue;
+                }
+                let node_config_file = Path::new(ctx.node_dir)
+                    .with_file_name(format!("{}-{}", ctx.chain_name, &node.domain))
+                    .join(NODE_CONFIG_FILE);
+                if !node_config_file.exists() {
+                    continue;
+                }
+                if let Ok(other) = load_node_config(node_config_file) {
+                    if other.chaincode.chaincode_port() == chaincode_port {
+                        println!(
+                            "chaincode_port {chaincode_port} is used by local node {}, set another one in node_config",
+                            &node.domain
+                        );
+                        return Err(Error::InvalidExecutorConfig);
+                    }
+                }
+            }
+        }
+        let executor_config = ExecutorChaincodeConfig {
+            domain: ctx.real_domain(),
+            executor_port,
+            metrics_port,
+            enable_metrics: node_config.enable_metrics,
+            log_config: ctx.log_config(EXECUTOR),
+            chaincode_port,
+            execute_timeout_ms: node_config.chaincode.execute_timeout_ms,
+        };
+        executor_config.write(config_file);
+        Ok(())
+    }
+
+    fn command(&self) -> Vec<String> {
+        vec![
+            "executor".to_string(),
+            "run".to_string(),
+            "-c".to_string(),
+            "/etc/cita-cloud/config/config.toml".to_string(),
+        ]
+    }
+
+    fn mount_account(&self) -> bool {
+        false
+    }
+
+    /// chaincode connects to executor by chaincode port
+    fn container_ports(&self, ctx: &NodeContext) -> Vec<ContainerPort> {
+        vec![
+            ContainerPort {
+                container_port: ctx.node_config.grpc_ports.executor_port as i32,
+                name: Some("grpc".to_string()),
+                protocol: Some("TCP".to_string()),
+                ..Default::default()
+            },
+            ContainerPort {
+                container_port: ctx.node_config.chaincode.chaincode_port() as i32,
+                name: Some("chaincode".to_string()),
+                protocol: Some("TCP".to_string()),
+                ..Default::default()
+            },
+        ]
+    }
+}
+
+#[cfg(test)]
+mod executor_chaincode_test {
+    use super::*;
+
+    #[test]
+    fn tuning_test() {
+        let mut tuning = ChaincodeTuning::default();
+        assert_eq!(tuning.chaincode_port(), DEFAULT_CHAINCODE_PORT);
+        assert!(tuning.check(50002, 60002));
+        tuning.chaincode_port = Some(50002);
+        assert!(!tuning.check(50002, 60002));
+        tuning.chaincode_port = Some(7053);
+        tuning.execute_timeout_ms = Some(0);
+        assert!(!tuning.check(50002, 60002));
+    }
+}
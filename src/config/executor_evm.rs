@@ -12,12 +12,47 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::constant::EXECUTOR_EVM;
-use crate::traits::TomlWriter;
+use crate::constant::{EXECUTOR, EXECUTOR_EVM};
+use crate::error::Error;
+use crate::registry::NodeContext;
+use crate::traits::{MicroServiceKind, TomlWriter};
+use k8s_openapi::api::core::v1::ContainerPort;
 use serde::{Deserialize, Serialize};
 
 use super::log_config::LogConfig;
 
+/// tuning of executor_evm set in node_config
+/// unset knobs are omitted so executor_evm uses its own defaults
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct EvmTuning {
+    // accept transactions and calls in the format of ethereum
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eth_compatibility: Option<bool>,
+    // max quota used by a call or estimate_quota
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub call_quota_limit: Option<u64>,
+    // port of debug endpoint like trace and state dump, unset means disabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub debug_port: Option<u16>,
+}
+
+impl EvmTuning {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// call_quota_limit must be positive and debug_port must not conflict with ports of executor
+    pub fn check(&self, executor_port: u16, metrics_port: u16) -> bool {
+        if self.call_quota_limit == Some(0) {
+            return false;
+        }
+        match self.debug_port {
+            Some(port) => port != 0 && port != executor_port && port != metrics_port,
+            None => true,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Clone, Deserialize)]
 pub struct ExecutorEvmConfig {
     pub domain: String,
@@ -25,29 +60,81 @@ pub struct ExecutorEvmConfig {
     pub metrics_port: u16,
     pub enable_metrics: bool,
     pub log_config: LogConfig,
+    #[serde(flatten)]
+    pub tuning: EvmTuning,
 }
 
-impl ExecutorEvmConfig {
-    pub fn new(
-        domain: String,
-        executor_port: u16,
-        metrics_port: u16,
-        enable_metrics: bool,
-        log_config: LogConfig,
-    ) -> Self {
-        Self {
-            domain,
+impl TomlWriter for ExecutorEvmConfig {
+    fn section(&self) -> String {
+        EXECUTOR_EVM.to_string()
+    }
+}
+
+pub struct ExecutorEvmKind;
+
+impl MicroServiceKind for ExecutorEvmKind {
+    fn image(&self) -> &str {
+        EXECUTOR_EVM
+    }
+
+    fn service(&self) -> &str {
+        EXECUTOR
+    }
+
+    fn section(&self) -> &str {
+        EXECUTOR_EVM
+    }
+
+    fn render(&self, ctx: &NodeContext, config_file: &str) -> Result<(), Error> {
+        let node_config = ctx.node_config;
+        let executor_port = node_config.grpc_ports.executor_port;
+        let metrics_port = node_config.metrics_ports.executor_metrics_port;
+        if !node_config.evm.check(executor_port, metrics_port) {
+            println!("invalid evm: call_quota_limit must be greater than 0, debug_port must be greater than 0 and differ from ports of executor");
+            return Err(Error::InvalidExecutorConfig);
+        }
+        let executor_config = ExecutorEvmConfig {
+            domain: ctx.real_domain(),
             executor_port,
             metrics_port,
-            enable_metrics,
-            log_config,
-        }
+            enable_metrics: node_config.enable_metrics,
+            log_config: ctx.log_config(EXECUTOR),
+            tuning: node_config.evm.clone(),
+        };
+        executor_config.write(config_file);
+        Ok(())
     }
-}
 
-impl TomlWriter for ExecutorEvmConfig {
-    fn section(&self) -> String {
-        EXECUTOR_EVM.to_string()
+    fn command(&self) -> Vec<String> {
+        vec![
+            "executor".to_string(),
+            "run".to_string(),
+            "-c".to_string(),
+            "/etc/cita-cloud/config/config.toml".to_string(),
+        ]
+    }
+
+    fn mount_account(&self) -> bool {
+        false
+    }
+
+    /// debug endpoint is exposed only when enabled
+    fn container_ports(&self, ctx: &NodeContext) -> Vec<ContainerPort> {
+        let mut ports = vec![ContainerPort {
+            container_port: ctx.node_config.grpc_ports.executor_port as i32,
+            name: Some("grpc".to_string()),
+            protocol: Some("TCP".to_string()),
+            ..Default::default()
+        }];
+        if let Some(debug_port) = ctx.node_config.evm.debug_port {
+            ports.push(ContainerPort {
+                container_port: debug_port as i32,
+                name: Some("debug".to_string()),
+                protocol: Some("TCP".to_string()),
+                ..Default::default()
+            });
+        }
+        ports
     }
 }
 
@@ -65,8 +152,25 @@ mod executor_test {
             metrics_port: 61232,
             enable_metrics: true,
             log_config: LogConfig::default(),
+            tuning: EvmTuning::default(),
         };
 
         config.write("example");
     }
+
+    #[test]
+    fn tuning_test() {
+        let mut tuning = EvmTuning {
+            eth_compatibility: Some(true),
+            ..Default::default()
+        };
+        assert!(tuning.check(50002, 60002));
+        tuning.call_quota_limit = Some(0);
+        assert!(!tuning.check(50002, 60002));
+        tuning.call_quota_limit = None;
+        tuning.debug_port = Some(50002);
+        assert!(!tuning.check(50002, 60002));
+        tuning.debug_port = Some(50005);
+        assert!(tuning.check(50002, 60002));
+    }
 }
@@ -13,6 +13,76 @@
 // limitations under the License.
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TracingExporter {
+    // deprecated jaeger agent over udp
+    #[default]
+    JaegerAgent,
+    OtlpGrpc,
+    OtlpHttp,
+}
+
+impl fmt::Display for TracingExporter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TracingExporter::JaegerAgent => write!(f, "jaeger-agent"),
+            TracingExporter::OtlpGrpc => write!(f, "otlp-grpc"),
+            TracingExporter::OtlpHttp => write!(f, "otlp-http"),
+        }
+    }
+}
+
+impl FromStr for TracingExporter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "jaeger-agent" => Ok(TracingExporter::JaegerAgent),
+            "otlp-grpc" => Ok(TracingExporter::OtlpGrpc),
+            "otlp-http" => Ok(TracingExporter::OtlpHttp),
+            _ => Err(format!(
+                "invalid tracing exporter {s}: should be jaeger-agent/otlp-grpc/otlp-http"
+            )),
+        }
+    }
+}
+
+fn default_sampling_ratio() -> f64 {
+    1.0
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TracingConfig {
+    #[serde(default)]
+    pub exporter: TracingExporter,
+    pub endpoint: String,
+    // ratio of traces sampled, in [0, 1]
+    #[serde(default = "default_sampling_ratio")]
+    pub sampling_ratio: f64,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub resource_attributes: BTreeMap<String, String>,
+}
+
+impl TracingConfig {
+    pub fn new(exporter: TracingExporter, endpoint: String) -> Self {
+        Self {
+            exporter,
+            endpoint,
+            sampling_ratio: default_sampling_ratio(),
+            resource_attributes: BTreeMap::new(),
+        }
+    }
+
+    pub fn check(&self) -> bool {
+        !self.endpoint.is_empty() && (0.0..=1.0).contains(&self.sampling_ratio)
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LogConfig {
@@ -20,7 +90,10 @@ pub struct LogConfig {
     pub filter: String,
     pub service_name: String,
     pub rolling_file_path: Option<String>,
+    // only set for jaeger agent, keep it for services which don't know tracing
     pub agent_endpoint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tracing: Option<TracingConfig>,
 }
 
 impl Default for LogConfig {
@@ -31,6 +104,109 @@ impl Default for LogConfig {
             service_name: Default::default(),
             rolling_file_path: Default::default(),
             agent_endpoint: Default::default(),
+            tracing: Default::default(),
         }
     }
 }
+
+const LEVELS: [&str; 6] = ["trace", "debug", "info", "warn", "error", "off"];
+
+pub fn check_level(level: &str) -> bool {
+    LEVELS.contains(&level.to_lowercase().as_str())
+}
+
+fn check_target(target: &str) -> bool {
+    !target.is_empty()
+        && target
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_:-.".contains(c))
+}
+
+// split directives by commas which are not in span fields
+fn split_directives(filter: &str) -> Option<Vec<&str>> {
+    let mut directives = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in filter.char_indices() {
+        match c {
+            '[' | '{' => depth += 1,
+            ']' | '}' => {
+                if depth == 0 {
+                    return None;
+                }
+                depth -= 1;
+            }
+            ',' if depth == 0 => {
+                directives.push(&filter[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return None;
+    }
+    directives.push(&filter[start..]);
+    Some(directives)
+}
+
+// span is [name{fields}], name and fields are optional
+fn check_span(span: &str) -> bool {
+    let Some(span) = span.strip_prefix('[').and_then(|s| s.strip_suffix(']')) else {
+        return false;
+    };
+    match span.split_once('{') {
+        Some((name, fields)) => {
+            (name.is_empty() || check_target(name))
+                && fields.strip_suffix('}').is_some_and(|f| !f.is_empty())
+        }
+        None => span.is_empty() || check_target(span),
+    }
+}
+
+// directive is target[span{fields}]=level, or a level, or a target
+fn check_directive(directive: &str) -> bool {
+    let (selector, level) = match directive.rfind('=') {
+        Some(i) if !directive[i..].contains(']') => (&directive[..i], Some(&directive[i + 1..])),
+        _ => (directive, None),
+    };
+    if level.is_some_and(|level| !check_level(level)) || selector.is_empty() {
+        return false;
+    }
+    match selector.find('[') {
+        Some(i) => (i == 0 || check_target(&selector[..i])) && check_span(&selector[i..]),
+        None => check_target(selector),
+    }
+}
+
+/// check filter by syntax of env-filter directives
+pub fn check_filter(filter: &str) -> bool {
+    split_directives(filter).is_some_and(|directives| {
+        directives
+            .iter()
+            .map(|directive| directive.trim())
+            .filter(|directive| !directive.is_empty())
+            .all(check_directive)
+    })
+}
+
+#[cfg(test)]
+mod log_config_test {
+    use super::*;
+
+    #[test]
+    fn filter_test() {
+        assert!(check_filter("info"));
+        assert!(check_filter("consensus=trace,info"));
+        assert!(check_filter("warn,consensus::engine=TRACE,tower-http=off"));
+        assert!(check_filter("consensus[sync{height=1,round=2}]=debug"));
+        assert!(check_filter("[sync]=debug"));
+        assert!(check_filter("consensus"));
+
+        assert!(!check_filter("consensus=verbose"));
+        assert!(!check_filter("=info"));
+        assert!(!check_filter("consensus[sync=debug"));
+        assert!(!check_filter("consensus[sync{}]=debug"));
+        assert!(!check_filter("consen sus=debug"));
+    }
+}
@@ -12,9 +12,16 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::constant::NETWORK_ZENOH;
-use crate::traits::TomlWriter;
+use crate::constant::{
+    CA_CERT_DIR, CERTS_DIR, CERT_PEM, CONSENSUS, CONTROLLER, KEY_PEM, NETWORK, NETWORK_ZENOH,
+};
+use crate::error::Error;
+use crate::registry::NodeContext;
+use crate::traits::{MicroServiceKind, TomlWriter};
+use crate::util::read_file;
+use k8s_openapi::api::core::v1::ContainerPort;
 use serde::{Deserialize, Serialize};
+use std::net::{Ipv4Addr, Ipv6Addr};
 
 use super::log_config::LogConfig;
 
@@ -32,6 +39,41 @@ pub struct ModuleConfig {
     pub port: u16,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CongestionControl {
+    Block,
+    Drop,
+}
+
+/// tuning of network_zenoh set in node_config
+/// unset knobs are omitted so network_zenoh uses its own defaults
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ZenohTuning {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connect_retry_period_init_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connect_retry_period_max_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scouting_multicast_enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scouting_multicast_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub congestion_control: Option<CongestionControl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub qos_enabled: Option<bool>,
+
+    // other options of network_zenoh, passed through as is
+    #[serde(flatten)]
+    pub extra: toml::Table,
+}
+
+impl ZenohTuning {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ZenohConfig {
     pub grpc_port: u16,
@@ -60,6 +102,23 @@ pub struct ZenohConfig {
     pub metrics_port: u16,
     pub enable_metrics: bool,
     pub log_config: LogConfig,
+
+    #[serde(flatten)]
+    pub tuning: ZenohTuning,
+}
+
+impl ZenohConfig {
+    /// keys in extra of tuning which would override generated options
+    pub fn conflicting_extra_keys(&self) -> Vec<String> {
+        let mut generated = self.clone();
+        let extra = std::mem::take(&mut generated.tuning.extra);
+        let generated = toml::Table::try_from(generated).unwrap();
+        extra
+            .keys()
+            .filter(|key| generated.contains_key(*key))
+            .cloned()
+            .collect()
+    }
 }
 
 impl TomlWriter for ZenohConfig {
@@ -67,3 +126,230 @@ impl TomlWriter for ZenohConfig {
         NETWORK_ZENOH.to_string()
     }
 }
+
+pub struct NetworkZenohKind;
+
+impl NetworkZenohKind {
+    // config peers decided by topology of chain
+    // if current node in k8s
+    // -- if same cluster and same namespace port is peer internal port, domain is svc name
+    // -- if same cluster and different namespace, port is peer internal port, domain is svc name
+    // -- if diffrent cluster and peer host is FQDN, port is peer port, domain is svc name
+    // -- if diffrent cluster and peer host is ip, port is peer internal port, domain is svc name
+    // if current node not in k8s, port is peer port, domain is peer host
+    fn peers(&self, ctx: &NodeContext) -> Vec<PeerConfig> {
+        let mut zenoh_peers: Vec<PeerConfig> = Vec::new();
+        for node_network_address in ctx.chain_config.peers(ctx.domain) {
+            if ctx.is_k8s() {
+                let peer_cluster_name = &node_network_address.cluster;
+                let peer_name_space = &node_network_address.name_space;
+                let peer_host = &node_network_address.host;
+                let is_peer_host_ip =
+                    peer_host.parse::<Ipv4Addr>().is_ok() || peer_host.parse::<Ipv6Addr>().is_ok();
+                let peer_port = node_network_address.port;
+                let peer_internal_port = ctx.chain_config.internal_port(node_network_address);
+                let peer_svc_name = format!("{}-{}", ctx.chain_name, &node_network_address.domain);
+
+                let same_cluster = *peer_cluster_name == ctx.node.cluster;
+                let same_name_space = *peer_name_space == ctx.node.name_space;
+
+                let peer_config = match (same_cluster, same_name_space, is_peer_host_ip) {
+                    (true, true, _) => PeerConfig {
+                        port: peer_internal_port,
+                        domain: peer_svc_name,
+                        protocol: node_network_address.protocol.to_string(),
+                    },
+                    (true, false, _) => PeerConfig {
+                        port: peer_internal_port,
+                        domain: peer_svc_name,
+                        protocol: node_network_address.protocol.to_string(),
+                    },
+                    (false, _, false) => PeerConfig {
+                        port: peer_port,
+                        domain: peer_svc_name,
+                        protocol: node_network_address.protocol.to_string(),
+                    },
+                    (false, _, true) => PeerConfig {
+                        port: peer_internal_port,
+                        domain: peer_svc_name,
+                        protocol: node_network_address.protocol.to_string(),
+                    },
+                };
+
+                zenoh_peers.push(peer_config);
+            } else {
+                // ipv6 host should be bracketed in zenoh endpoint
+                let peer_host = &node_network_address.host;
+                let domain = if peer_host.parse::<Ipv6Addr>().is_ok() {
+                    format!("[{peer_host}]")
+                } else {
+                    peer_host.clone()
+                };
+                zenoh_peers.push(PeerConfig {
+                    port: node_network_address.port,
+                    domain,
+                    protocol: node_network_address.protocol.to_string(),
+                });
+            }
+        }
+        zenoh_peers
+    }
+}
+
+impl MicroServiceKind for NetworkZenohKind {
+    fn image(&self) -> &str {
+        NETWORK_ZENOH
+    }
+
+    fn service(&self) -> &str {
+        NETWORK
+    }
+
+    fn section(&self) -> &str {
+        NETWORK_ZENOH
+    }
+
+    fn render(&self, ctx: &NodeContext, config_file: &str) -> Result<(), Error> {
+        let node_config = ctx.node_config;
+
+        // load cert
+        let ca_cert = read_file(format!("{}/{}/{}", ctx.node_dir, CA_CERT_DIR, CERT_PEM)).unwrap();
+        let cert = read_file(format!(
+            "{}/{}/{}/{}",
+            ctx.node_dir, CERTS_DIR, ctx.domain, CERT_PEM
+        ))
+        .unwrap();
+        let key = read_file(format!(
+            "{}/{}/{}/{}",
+            ctx.node_dir, CERTS_DIR, ctx.domain, KEY_PEM
+        ))
+        .unwrap();
+
+        // modules
+        let modules = vec![
+            ModuleConfig {
+                module_name: CONSENSUS.to_string(),
+                hostname: "localhost".to_string(),
+                port: node_config.grpc_ports.consensus_port,
+            },
+            ModuleConfig {
+                module_name: CONTROLLER.to_string(),
+                hostname: "localhost".to_string(),
+                port: node_config.grpc_ports.controller_port,
+            },
+        ];
+
+        let network_config = ZenohConfig {
+            port: if ctx.is_k8s() {
+                ctx.internal_port()
+            } else {
+                ctx.node.port
+            },
+            grpc_port: node_config.grpc_ports.network_port,
+            ca_cert,
+            cert,
+            priv_key: key,
+            peers: self.peers(ctx),
+            domain: ctx.real_domain(),
+            protocol: ctx.node.protocol.to_string(),
+            node_address: ctx.node_address(),
+            validator_address: ctx.validator_address(),
+            chain_id: ctx.chain_config.system_config.chain_id.clone(),
+            modules,
+            metrics_port: node_config.metrics_ports.network_metrics_port,
+            enable_metrics: node_config.enable_metrics,
+            log_config: ctx.log_config(NETWORK),
+            tuning: node_config.zenoh.clone(),
+        };
+        let conflicting_keys = network_config.conflicting_extra_keys();
+        if !conflicting_keys.is_empty() {
+            println!("zenoh options can't override generated options: {conflicting_keys:?}");
+            return Err(Error::InvalidNetworkConfig);
+        }
+        network_config.write(config_file);
+        Ok(())
+    }
+
+    fn command(&self) -> Vec<String> {
+        vec![
+            "network".to_string(),
+            "run".to_string(),
+            "-c".to_string(),
+            "/etc/cita-cloud/config/config.toml".to_string(),
+        ]
+    }
+
+    fn container_ports(&self, ctx: &NodeContext) -> Vec<ContainerPort> {
+        vec![
+            ContainerPort {
+                container_port: ctx.internal_port() as i32,
+                name: Some("network".to_string()),
+                // quic use UDP, tls and tcp use TCP
+                protocol: Some(ctx.node.protocol.k8s_protocol().to_string()),
+                ..Default::default()
+            },
+            ContainerPort {
+                container_port: ctx.node_config.grpc_ports.network_port as i32,
+                name: Some("grpc".to_string()),
+                protocol: Some("TCP".to_string()),
+                ..Default::default()
+            },
+        ]
+    }
+}
+
+#[cfg(test)]
+mod network_zenoh_test {
+    use super::*;
+
+    #[test]
+    fn tuning_test() {
+        assert_eq!(
+            toml::to_string(&ZenohTuning::default()).unwrap(),
+            "".to_string()
+        );
+
+        let tuning: ZenohTuning = toml::from_str(
+            r#"
+            qos_enabled = false
+            congestion_control = "drop"
+            open_timeout_ms = 5000
+            grpc_port = 51000
+            "#,
+        )
+        .unwrap();
+        assert_eq!(tuning.qos_enabled, Some(false));
+        assert_eq!(tuning.congestion_control, Some(CongestionControl::Drop));
+        assert_eq!(tuning.connect_retry_period_init_ms, None);
+        assert_eq!(tuning.extra.len(), 2);
+
+        let mut config = ZenohConfig {
+            grpc_port: 50000,
+            domain: "test-chain-node0".to_string(),
+            protocol: "quic".to_string(),
+            port: 40000,
+            ca_cert: "".to_string(),
+            cert: "".to_string(),
+            priv_key: "".to_string(),
+            peers: vec![],
+            modules: vec![],
+            node_address: "node_address".to_string(),
+            validator_address: "validator_address".to_string(),
+            chain_id: "".to_string(),
+            metrics_port: 60000,
+            enable_metrics: true,
+            log_config: LogConfig::default(),
+            tuning,
+        };
+        assert_eq!(
+            config.conflicting_extra_keys(),
+            vec!["grpc_port".to_string()]
+        );
+        config.tuning.extra.remove("grpc_port");
+
+        let value = toml::Table::try_from(config).unwrap();
+        assert_eq!(value["open_timeout_ms"].as_integer(), Some(5000));
+        assert_eq!(value["congestion_control"].as_str(), Some("drop"));
+        assert!(!value.contains_key("connect_retry_period_init_ms"));
+    }
+}
@@ -12,8 +12,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::config::consensus_raft::RaftTuning;
+use crate::config::executor_chaincode::ChaincodeTuning;
+use crate::config::executor_evm::EvmTuning;
+use crate::config::log_config::{
+    check_filter, check_level, LogConfig, TracingConfig, TracingExporter,
+};
+use crate::config::network_zenoh::ZenohTuning;
+use crate::config::storage_opendal::{StorageBackend, StorageTuning};
+use crate::constant::{CONSENSUS, CONTROLLER, EXECUTOR, NETWORK, SCHEMA_VERSION, STORAGE};
+use crate::error::Error;
 use crate::util::check_address;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const LOG_SERVICES: [&str; 5] = [NETWORK, CONSENSUS, EXECUTOR, STORAGE, CONTROLLER];
 
 #[derive(Debug, Serialize, Clone, Deserialize)]
 pub struct GrpcPorts {
@@ -156,90 +169,6 @@ impl MetricsPortsBuilder {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CloudStorage {
-    pub access_key_id: String,
-    pub secret_access_key: String,
-    pub endpoint: String,
-    pub bucket: String,
-    pub service_type: String,
-    pub root: String,
-    pub region: String,
-}
-
-pub struct CloudStorageBuilder {
-    pub access_key_id: String,
-    pub secret_access_key: String,
-    pub endpoint: String,
-    pub bucket: String,
-    pub service_type: String,
-    pub root: String,
-    pub region: String,
-}
-
-impl Default for CloudStorageBuilder {
-    fn default() -> Self {
-        Self {
-            access_key_id: "".to_string(),
-            secret_access_key: "".to_string(),
-            endpoint: "".to_string(),
-            bucket: "".to_string(),
-            service_type: "".to_string(),
-            root: "".to_string(),
-            region: "".to_string(),
-        }
-    }
-}
-
-impl CloudStorageBuilder {
-    pub fn access_key_id(&mut self, access_key_id: String) -> &mut CloudStorageBuilder {
-        self.access_key_id = access_key_id;
-        self
-    }
-
-    pub fn secret_access_key(&mut self, secret_access_key: String) -> &mut CloudStorageBuilder {
-        self.secret_access_key = secret_access_key;
-        self
-    }
-
-    pub fn endpoint(&mut self, endpoint: String) -> &mut CloudStorageBuilder {
-        self.endpoint = endpoint;
-        self
-    }
-
-    pub fn bucket(&mut self, bucket: String) -> &mut CloudStorageBuilder {
-        self.bucket = bucket;
-        self
-    }
-
-    pub fn service_type(&mut self, service_type: String) -> &mut CloudStorageBuilder {
-        self.service_type = service_type;
-        self
-    }
-
-    pub fn root(&mut self, root: String) -> &mut CloudStorageBuilder {
-        self.root = root;
-        self
-    }
-
-    pub fn region(&mut self, region: String) -> &mut CloudStorageBuilder {
-        self.region = region;
-        self
-    }
-
-    pub fn build(&self) -> CloudStorage {
-        CloudStorage {
-            access_key_id: self.access_key_id.clone(),
-            secret_access_key: self.secret_access_key.clone(),
-            endpoint: self.endpoint.clone(),
-            bucket: self.bucket.clone(),
-            service_type: self.service_type.clone(),
-            root: self.root.clone(),
-            region: self.region.clone(),
-        }
-    }
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(default)]
 pub struct ExportConfig {
@@ -247,19 +176,154 @@ pub struct ExportConfig {
     pub chain_name: String, // use citacloud.{chain_name} as prefix of topic
 }
 
+/// log config of a micro service, unset fields fall back to global ones in node config
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ServiceLogConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_level: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rolling_file_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_endpoint: Option<String>,
+}
+
 #[derive(Debug, Serialize, Clone, Deserialize)]
 pub struct NodeConfig {
+    // missing in node_config of old version, upgraded by migrations when loaded
+    #[serde(default)]
+    pub schema_version: u64,
     pub grpc_ports: GrpcPorts,
     pub metrics_ports: MetricsPorts,
     pub log_level: String,
     pub log_file_path: Option<String>,
+    // deprecated, use tracing instead
     pub jaeger_agent_endpoint: Option<String>,
     pub account: String,
     pub enable_metrics: bool,
     pub is_danger: bool,
     pub enable_tx_persistence: bool,
-    pub cloud_storage: CloudStorage,
+    // no backend means storage_opendal runs without cloud storage
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage_backend: Option<StorageBackend>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "StorageTuning::is_default")]
+    pub storage: StorageTuning,
     pub exporter: ExportConfig,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tracing: Option<TracingConfig>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "ZenohTuning::is_default")]
+    pub zenoh: ZenohTuning,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "RaftTuning::is_default")]
+    pub raft: RaftTuning,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "EvmTuning::is_default")]
+    pub evm: EvmTuning,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "ChaincodeTuning::is_default")]
+    pub chaincode: ChaincodeTuning,
+    // key is name of micro service: network/consensus/executor/storage/controller
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub service_log: BTreeMap<String, ServiceLogConfig>,
+}
+
+impl NodeConfig {
+    /// log config of micro service, per-service config takes precedence
+    /// chain name and domain of node are added into resource attributes of tracing
+    pub fn log_config(&self, service: &str, chain_name: &str, domain: &str) -> LogConfig {
+        let service_log = self.service_log.get(service).cloned().unwrap_or_default();
+        let max_level = service_log
+            .max_level
+            .unwrap_or_else(|| self.log_level.clone());
+
+        // jaeger_agent_endpoint is used only if tracing is not set
+        let mut tracing = self.tracing.clone().or_else(|| {
+            self.jaeger_agent_endpoint
+                .clone()
+                .map(|endpoint| TracingConfig::new(TracingExporter::JaegerAgent, endpoint))
+        });
+        // per-service agent endpoint only replaces endpoint of jaeger agent
+        if let Some(endpoint) = service_log.agent_endpoint {
+            match &mut tracing {
+                Some(tracing) if tracing.exporter == TracingExporter::JaegerAgent => {
+                    tracing.endpoint = endpoint
+                }
+                Some(_) => {}
+                None => tracing = Some(TracingConfig::new(TracingExporter::JaegerAgent, endpoint)),
+            }
+        }
+        if let Some(tracing) = &mut tracing {
+            tracing
+                .resource_attributes
+                .entry("chain.name".to_string())
+                .or_insert_with(|| chain_name.to_string());
+            tracing
+                .resource_attributes
+                .entry("node.domain".to_string())
+                .or_insert_with(|| domain.to_string());
+        }
+
+        LogConfig {
+            filter: service_log.filter.unwrap_or_else(|| max_level.clone()),
+            max_level,
+            service_name: service.to_owned(),
+            rolling_file_path: service_log
+                .rolling_file_path
+                .or_else(|| self.log_file_path.clone()),
+            agent_endpoint: tracing
+                .as_ref()
+                .filter(|tracing| tracing.exporter == TracingExporter::JaegerAgent)
+                .map(|tracing| tracing.endpoint.clone()),
+            tracing,
+        }
+    }
+
+    pub fn check_log_config(&self) -> Result<(), Error> {
+        if let Some(tracing) = &self.tracing {
+            if !tracing.check() {
+                println!(
+                    "invalid tracing: endpoint must be set and sampling_ratio must be in [0, 1]"
+                );
+                return Err(Error::InvalidLogConfig);
+            }
+        }
+        for (service, service_log) in &self.service_log {
+            if !LOG_SERVICES.contains(&service.as_str()) {
+                println!("unknown service in service_log: {service}");
+                return Err(Error::InvalidLogConfig);
+            }
+            if let Some(max_level) = &service_log.max_level {
+                if !check_level(max_level) {
+                    println!("invalid max_level of {service}: {max_level}");
+                    return Err(Error::InvalidLogConfig);
+                }
+            }
+            if let Some(filter) = &service_log.filter {
+                if !check_filter(filter) {
+                    println!("invalid filter of {service}: {filter}");
+                    return Err(Error::InvalidLogConfig);
+                }
+            }
+            if let (Some(agent_endpoint), Some(tracing)) =
+                (&service_log.agent_endpoint, &self.tracing)
+            {
+                if tracing.exporter != TracingExporter::JaegerAgent {
+                    println!(
+                        "agent_endpoint of {service}: {agent_endpoint} can't be used with {} exporter",
+                        tracing.exporter
+                    );
+                    return Err(Error::InvalidLogConfig);
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 pub struct NodeConfigBuilder {
@@ -272,8 +336,15 @@ pub struct NodeConfigBuilder {
     pub enable_metrics: bool,
     pub is_danger: bool,
     pub enable_tx_persistence: bool,
-    pub cloud_storage: CloudStorage,
+    pub storage_backend: Option<StorageBackend>,
+    pub storage: StorageTuning,
     pub exporter: ExportConfig,
+    pub tracing: Option<TracingConfig>,
+    pub zenoh: ZenohTuning,
+    pub raft: RaftTuning,
+    pub evm: EvmTuning,
+    pub chaincode: ChaincodeTuning,
+    pub service_log: BTreeMap<String, ServiceLogConfig>,
 }
 
 impl Default for NodeConfigBuilder {
@@ -288,8 +359,15 @@ impl Default for NodeConfigBuilder {
             enable_metrics: true,
             is_danger: false,
             enable_tx_persistence: false,
-            cloud_storage: CloudStorageBuilder::default().build(),
+            storage_backend: None,
+            storage: StorageTuning::default(),
             exporter: ExportConfig::default(),
+            tracing: None,
+            zenoh: ZenohTuning::default(),
+            raft: RaftTuning::default(),
+            evm: EvmTuning::default(),
+            chaincode: ChaincodeTuning::default(),
+            service_log: BTreeMap::new(),
         }
     }
 }
@@ -343,8 +421,11 @@ impl NodeConfigBuilder {
         self
     }
 
-    pub fn cloud_storage(&mut self, cloud_storage: CloudStorage) -> &mut NodeConfigBuilder {
-        self.cloud_storage = cloud_storage;
+    pub fn storage_backend(
+        &mut self,
+        storage_backend: Option<StorageBackend>,
+    ) -> &mut NodeConfigBuilder {
+        self.storage_backend = storage_backend;
         self
     }
 
@@ -353,8 +434,27 @@ impl NodeConfigBuilder {
         self
     }
 
+    pub fn tracing(&mut self, tracing: Option<TracingConfig>) -> &mut NodeConfigBuilder {
+        self.tracing = tracing;
+        self
+    }
+
+    pub fn chaincode(&mut self, chaincode: ChaincodeTuning) -> &mut NodeConfigBuilder {
+        self.chaincode = chaincode;
+        self
+    }
+
+    pub fn service_log(
+        &mut self,
+        service_log: BTreeMap<String, ServiceLogConfig>,
+    ) -> &mut NodeConfigBuilder {
+        self.service_log = service_log;
+        self
+    }
+
     pub fn build(&self) -> NodeConfig {
         NodeConfig {
+            schema_version: SCHEMA_VERSION,
             grpc_ports: self.grpc_ports.clone(),
             metrics_ports: self.metrics_ports.clone(),
             log_level: self.log_level.clone(),
@@ -364,8 +464,60 @@ impl NodeConfigBuilder {
             enable_metrics: self.enable_metrics,
             is_danger: self.is_danger,
             enable_tx_persistence: self.enable_tx_persistence,
-            cloud_storage: self.cloud_storage.clone(),
+            storage_backend: self.storage_backend.clone(),
+            storage: self.storage.clone(),
             exporter: self.exporter.clone(),
+            tracing: self.tracing.clone(),
+            zenoh: self.zenoh.clone(),
+            raft: self.raft.clone(),
+            evm: self.evm.clone(),
+            chaincode: self.chaincode.clone(),
+            service_log: self.service_log.clone(),
         }
     }
 }
+
+#[cfg(test)]
+mod node_config_test {
+    use super::*;
+
+    #[test]
+    fn log_config_test() {
+        let mut node_config = NodeConfigBuilder::default().build();
+        node_config.service_log.insert(
+            "consensus".to_string(),
+            ServiceLogConfig {
+                agent_endpoint: Some("127.0.0.1:6831".to_string()),
+                ..Default::default()
+            },
+        );
+
+        // agent endpoint of service without tracing
+        let log_config = node_config.log_config("consensus", "test-chain", "node0");
+        assert_eq!(log_config.agent_endpoint.as_deref(), Some("127.0.0.1:6831"));
+        assert!(node_config.check_log_config().is_ok());
+
+        // replaces endpoint of jaeger agent
+        node_config.tracing = Some(TracingConfig::new(
+            TracingExporter::JaegerAgent,
+            "127.0.0.1:6832".to_string(),
+        ));
+        let log_config = node_config.log_config("consensus", "test-chain", "node0");
+        assert_eq!(log_config.tracing.unwrap().endpoint, "127.0.0.1:6831");
+        let log_config = node_config.log_config("network", "test-chain", "node0");
+        assert_eq!(log_config.tracing.unwrap().endpoint, "127.0.0.1:6832");
+
+        // otlp endpoint is kept
+        node_config.tracing = Some(TracingConfig::new(
+            TracingExporter::OtlpGrpc,
+            "http://127.0.0.1:4317".to_string(),
+        ));
+        let log_config = node_config.log_config("consensus", "test-chain", "node0");
+        assert_eq!(
+            log_config.tracing.unwrap().endpoint,
+            "http://127.0.0.1:4317"
+        );
+        assert_eq!(log_config.agent_endpoint, None);
+        assert!(node_config.check_log_config().is_err());
+    }
+}
@@ -12,12 +12,373 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::constant::STORAGE_OPENDAL;
-use crate::traits::TomlWriter;
+use crate::constant::{STORAGE, STORAGE_OPENDAL};
+use crate::error::Error;
+use crate::registry::NodeContext;
+use crate::traits::{MicroServiceKind, TomlWriter};
+use k8s_openapi::api::core::v1::{EnvVar, EnvVarSource, SecretKeySelector};
 use serde::{Deserialize, Serialize};
 
 use super::log_config::LogConfig;
-use super::node_config::{CloudStorage, ExportConfig};
+use super::node_config::ExportConfig;
+
+/// access key of cloud storage set in node_config, kept out of generated configs unless plain text
+/// `env:NAME` refers to environment variable NAME of storage container
+/// `file:PATH` refers to file at absolute PATH in storage container
+/// `secret:NAME/KEY` refers to KEY of k8s secret NAME, which is injected into env of storage container
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum Credential {
+    Plain(String),
+    Env(String),
+    File(String),
+    Secret { name: String, key: String },
+}
+
+impl From<String> for Credential {
+    fn from(s: String) -> Self {
+        if let Some(name) = s.strip_prefix("env:") {
+            Credential::Env(name.to_string())
+        } else if let Some(path) = s.strip_prefix("file:") {
+            Credential::File(path.to_string())
+        } else if let Some(secret) = s.strip_prefix("secret:") {
+            let (name, key) = secret.split_once('/').unwrap_or((secret, ""));
+            Credential::Secret {
+                name: name.to_string(),
+                key: key.to_string(),
+            }
+        } else {
+            Credential::Plain(s)
+        }
+    }
+}
+
+impl From<Credential> for String {
+    fn from(credential: Credential) -> Self {
+        match credential {
+            Credential::Plain(value) => value,
+            Credential::Env(name) => format!("env:{name}"),
+            Credential::File(path) => format!("file:{path}"),
+            Credential::Secret { name, key } => format!("secret:{name}/{key}"),
+        }
+    }
+}
+
+fn is_env_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl Credential {
+    fn check(&self, field: &str) -> Result<(), String> {
+        match self {
+            Credential::Plain(value) if value.is_empty() => Err(format!("{field} is required")),
+            Credential::Env(name) if !is_env_name(name) => {
+                Err(format!("invalid env name of {field}: {name}"))
+            }
+            Credential::File(path) if !path.starts_with('/') => {
+                Err(format!("path of {field} must be absolute: {path}"))
+            }
+            Credential::Secret { name, key }
+                if name.is_empty()
+                    || key.is_empty()
+                    || !name.chars().all(|c| {
+                        c.is_ascii_lowercase() || c.is_ascii_digit() || "-.".contains(c)
+                    })
+                    || !key
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c)) =>
+            {
+                Err(format!(
+                    "invalid secret of {field}: {name}/{key}, should be secret:NAME/KEY"
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// env of storage container which the credential is read from
+    /// secret is injected as STORAGE_{FIELD}
+    pub fn env_name(&self, field: &str) -> Option<String> {
+        match self {
+            Credential::Env(name) => Some(name.clone()),
+            Credential::Secret { .. } => Some(format!("STORAGE_{}", field.to_uppercase())),
+            _ => None,
+        }
+    }
+
+    /// value in config.toml, refs are rendered as placeholders ${NAME} or ${file:PATH}
+    pub fn render(&self, field: &str) -> String {
+        match self {
+            Credential::Plain(value) => value.clone(),
+            Credential::File(path) => format!("${{file:{path}}}"),
+            _ => format!("${{{}}}", self.env_name(field).unwrap()),
+        }
+    }
+}
+
+/// bucket of s3 compatible object storage
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObjectStorage {
+    #[serde(default)]
+    pub endpoint: String,
+    pub bucket: String,
+    #[serde(default)]
+    pub root: String,
+    #[serde(default)]
+    pub region: String,
+    pub access_key_id: Credential,
+    pub secret_access_key: Credential,
+}
+
+/// container of azure blob storage
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AzblobStorage {
+    pub endpoint: String,
+    pub container: String,
+    #[serde(default)]
+    pub root: String,
+    pub account_name: Credential,
+    pub account_key: Credential,
+}
+
+/// backend of storage_opendal set in node_config, tagged by `type`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum StorageBackend {
+    // local filesystem
+    Fs { root: String },
+    // aws s3
+    S3(ObjectStorage),
+    // aliyun
+    Oss(ObjectStorage),
+    // huawei
+    Obs(ObjectStorage),
+    // tencent
+    Cos(ObjectStorage),
+    // azure
+    Azblob(AzblobStorage),
+}
+
+fn required(value: &str, field: &str) -> Result<(), String> {
+    if value.is_empty() {
+        Err(format!("{field} is required"))
+    } else {
+        Ok(())
+    }
+}
+
+impl StorageBackend {
+    /// credentials of backend with their field names
+    pub fn credentials(&self) -> Vec<(&str, &Credential)> {
+        match self {
+            StorageBackend::Fs { .. } => vec![],
+            StorageBackend::S3(storage)
+            | StorageBackend::Oss(storage)
+            | StorageBackend::Obs(storage)
+            | StorageBackend::Cos(storage) => vec![
+                ("access_key_id", &storage.access_key_id),
+                ("secret_access_key", &storage.secret_access_key),
+            ],
+            StorageBackend::Azblob(storage) => vec![
+                ("account_name", &storage.account_name),
+                ("account_key", &storage.account_key),
+            ],
+        }
+    }
+
+    pub fn service_type(&self) -> &str {
+        match self {
+            StorageBackend::Fs { .. } => "fs",
+            StorageBackend::S3(_) => "s3",
+            StorageBackend::Oss(_) => "oss",
+            StorageBackend::Obs(_) => "obs",
+            StorageBackend::Cos(_) => "cos",
+            StorageBackend::Azblob(_) => "azblob",
+        }
+    }
+
+    /// check required fields of backend
+    pub fn check(&self) -> Result<(), String> {
+        match self {
+            StorageBackend::Fs { root } => required(root, "root"),
+            StorageBackend::S3(storage) => {
+                required(&storage.bucket, "bucket")?;
+                required(&storage.region, "region")?;
+                storage.access_key_id.check("access_key_id")?;
+                storage.secret_access_key.check("secret_access_key")
+            }
+            StorageBackend::Oss(storage)
+            | StorageBackend::Obs(storage)
+            | StorageBackend::Cos(storage) => {
+                required(&storage.endpoint, "endpoint")?;
+                required(&storage.bucket, "bucket")?;
+                storage.access_key_id.check("access_key_id")?;
+                storage.secret_access_key.check("secret_access_key")
+            }
+            StorageBackend::Azblob(storage) => {
+                required(&storage.endpoint, "endpoint")?;
+                required(&storage.container, "container")?;
+                storage.account_name.check("account_name")?;
+                storage.account_key.check("account_key")
+            }
+        }
+    }
+
+    /// flat cloud_storage section read by storage_opendal
+    pub fn to_cloud_storage(&self) -> CloudStorage {
+        let mut builder = CloudStorageBuilder::default();
+        builder.service_type(self.service_type().to_string());
+        match self {
+            StorageBackend::Fs { root } => {
+                builder.root(root.clone());
+            }
+            StorageBackend::S3(storage)
+            | StorageBackend::Oss(storage)
+            | StorageBackend::Obs(storage)
+            | StorageBackend::Cos(storage) => {
+                builder
+                    .endpoint(storage.endpoint.clone())
+                    .bucket(storage.bucket.clone())
+                    .root(storage.root.clone())
+                    .region(storage.region.clone())
+                    .access_key_id(storage.access_key_id.render("access_key_id"))
+                    .secret_access_key(storage.secret_access_key.render("secret_access_key"));
+            }
+            StorageBackend::Azblob(storage) => {
+                builder
+                    .endpoint(storage.endpoint.clone())
+                    .bucket(storage.container.clone())
+                    .root(storage.root.clone())
+                    .access_key_id(storage.account_name.render("account_name"))
+                    .secret_access_key(storage.account_key.render("account_key"));
+            }
+        }
+        builder.build()
+    }
+}
+
+/// flat cloud_storage section of storage_opendal config, rendered from storage_backend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloudStorage {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub endpoint: String,
+    pub bucket: String,
+    pub service_type: String,
+    pub root: String,
+    pub region: String,
+}
+
+pub struct CloudStorageBuilder {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub endpoint: String,
+    pub bucket: String,
+    pub service_type: String,
+    pub root: String,
+    pub region: String,
+}
+
+impl Default for CloudStorageBuilder {
+    fn default() -> Self {
+        Self {
+            access_key_id: "".to_string(),
+            secret_access_key: "".to_string(),
+            endpoint: "".to_string(),
+            bucket: "".to_string(),
+            service_type: "".to_string(),
+            root: "".to_string(),
+            region: "".to_string(),
+        }
+    }
+}
+
+impl CloudStorageBuilder {
+    pub fn access_key_id(&mut self, access_key_id: String) -> &mut CloudStorageBuilder {
+        self.access_key_id = access_key_id;
+        self
+    }
+
+    pub fn secret_access_key(&mut self, secret_access_key: String) -> &mut CloudStorageBuilder {
+        self.secret_access_key = secret_access_key;
+        self
+    }
+
+    pub fn endpoint(&mut self, endpoint: String) -> &mut CloudStorageBuilder {
+        self.endpoint = endpoint;
+        self
+    }
+
+    pub fn bucket(&mut self, bucket: String) -> &mut CloudStorageBuilder {
+        self.bucket = bucket;
+        self
+    }
+
+    pub fn service_type(&mut self, service_type: String) -> &mut CloudStorageBuilder {
+        self.service_type = service_type;
+        self
+    }
+
+    pub fn root(&mut self, root: String) -> &mut CloudStorageBuilder {
+        self.root = root;
+        self
+    }
+
+    pub fn region(&mut self, region: String) -> &mut CloudStorageBuilder {
+        self.region = region;
+        self
+    }
+
+    pub fn build(&self) -> CloudStorage {
+        CloudStorage {
+            access_key_id: self.access_key_id.clone(),
+            secret_access_key: self.secret_access_key.clone(),
+            endpoint: self.endpoint.clone(),
+            bucket: self.bucket.clone(),
+            service_type: self.service_type.clone(),
+            root: self.root.clone(),
+            region: self.region.clone(),
+        }
+    }
+}
+
+/// tuning of storage_opendal set in node_config
+/// unset knobs are omitted so storage_opendal uses its own defaults
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct StorageTuning {
+    // capacity of in-memory cache layer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub l1_capacity: Option<u64>,
+    // capacity of local layer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub l2_capacity: Option<u64>,
+    // interval in seconds to backup local layer to cloud storage
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup_interval: Option<u64>,
+    // interval in seconds to remove backed up blocks from local layer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retreat_interval: Option<u64>,
+}
+
+impl StorageTuning {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// all knobs must be positive
+    pub fn check(&self) -> bool {
+        [
+            self.l1_capacity,
+            self.l2_capacity,
+            self.backup_interval,
+            self.retreat_interval,
+        ]
+        .iter()
+        .all(|knob| *knob != Some(0))
+    }
+}
 
 #[derive(Debug, Serialize, Clone, Deserialize)]
 pub struct StorageOpendalConfig {
@@ -29,32 +390,191 @@ pub struct StorageOpendalConfig {
     // cloud storage
     pub cloud_storage: CloudStorage,
     pub exporter: ExportConfig,
+    #[serde(flatten)]
+    pub tuning: StorageTuning,
 }
 
-impl StorageOpendalConfig {
-    pub fn new(
-        domain: String,
-        storage_port: u16,
-        metrics_port: u16,
-        enable_metrics: bool,
-        log_config: LogConfig,
-        cloud_storage: CloudStorage,
-        exporter: ExportConfig,
-    ) -> Self {
-        Self {
-            domain,
-            storage_port,
-            metrics_port,
-            enable_metrics,
-            log_config,
-            cloud_storage,
-            exporter,
+impl TomlWriter for StorageOpendalConfig {
+    fn section(&self) -> String {
+        STORAGE_OPENDAL.to_string()
+    }
+}
+
+pub struct StorageOpendalKind;
+
+impl MicroServiceKind for StorageOpendalKind {
+    fn image(&self) -> &str {
+        STORAGE_OPENDAL
+    }
+
+    fn service(&self) -> &str {
+        STORAGE
+    }
+
+    fn section(&self) -> &str {
+        STORAGE_OPENDAL
+    }
+
+    fn render(&self, ctx: &NodeContext, config_file: &str) -> Result<(), Error> {
+        let node_config = ctx.node_config;
+        if !node_config.storage.check() {
+            println!("invalid storage: capacities and intervals must be greater than 0");
+            return Err(Error::InvalidStorageConfig);
         }
+        // no backend means storage_opendal runs without cloud storage
+        let cloud_storage = match &node_config.storage_backend {
+            Some(backend) => {
+                backend.check().map_err(|e| {
+                    println!("invalid {} storage_backend: {e}", backend.service_type());
+                    Error::InvalidStorageConfig
+                })?;
+                backend.to_cloud_storage()
+            }
+            None => CloudStorageBuilder::default().build(),
+        };
+        let storage_config = StorageOpendalConfig {
+            domain: ctx.real_domain(),
+            storage_port: node_config.grpc_ports.storage_port,
+            metrics_port: node_config.metrics_ports.storage_metrics_port,
+            enable_metrics: node_config.enable_metrics,
+            log_config: ctx.log_config(STORAGE),
+            cloud_storage,
+            exporter: node_config.exporter.clone(),
+            tuning: node_config.storage.clone(),
+        };
+        storage_config.write(config_file);
+        Ok(())
+    }
+
+    fn command(&self) -> Vec<String> {
+        vec![
+            "storage".to_string(),
+            "run".to_string(),
+            "-c".to_string(),
+            "/etc/cita-cloud/config/config.toml".to_string(),
+        ]
+    }
+
+    fn mount_account(&self) -> bool {
+        false
+    }
+
+    /// credentials referred by secret:NAME/KEY
+    fn env(&self, ctx: &NodeContext) -> Vec<EnvVar> {
+        let Some(backend) = &ctx.node_config.storage_backend else {
+            return vec![];
+        };
+        backend
+            .credentials()
+            .into_iter()
+            .filter_map(|(field, credential)| match credential {
+                Credential::Secret { name, key } => Some(EnvVar {
+                    name: credential.env_name(field).unwrap(),
+                    value_from: Some(EnvVarSource {
+                        secret_key_ref: Some(SecretKeySelector {
+                            name: name.clone(),
+                            key: key.clone(),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                _ => None,
+            })
+            .collect()
     }
 }
 
-impl TomlWriter for StorageOpendalConfig {
-    fn section(&self) -> String {
-        STORAGE_OPENDAL.to_string()
+#[cfg(test)]
+mod storage_opendal_test {
+    use super::*;
+
+    #[test]
+    fn backend_test() {
+        let backend: StorageBackend = toml::from_str(
+            r#"
+            type = "s3"
+            bucket = "chain"
+            region = "us-east-1"
+            access_key_id = "env:S3_ACCESS_KEY_ID"
+            secret_access_key = "secret"
+            "#,
+        )
+        .unwrap();
+        assert!(backend.check().is_ok());
+        let cloud_storage = backend.to_cloud_storage();
+        assert_eq!(cloud_storage.service_type, "s3");
+        assert_eq!(cloud_storage.access_key_id, "${S3_ACCESS_KEY_ID}");
+        assert_eq!(cloud_storage.secret_access_key, "secret");
+        let table = toml::Table::try_from(&backend).unwrap();
+        assert_eq!(
+            table["access_key_id"].as_str(),
+            Some("env:S3_ACCESS_KEY_ID")
+        );
+
+        let StorageBackend::S3(mut storage) = backend else {
+            unreachable!()
+        };
+        storage.region = String::new();
+        assert!(StorageBackend::S3(storage.clone()).check().is_err());
+        // endpoint is required by other object storages instead of region
+        assert!(StorageBackend::Oss(storage.clone()).check().is_err());
+        storage.endpoint = "oss-cn-hangzhou.aliyuncs.com".to_string();
+        assert!(StorageBackend::Oss(storage.clone()).check().is_ok());
+        storage.access_key_id = Credential::Env("1KEY".to_string());
+        assert!(StorageBackend::Oss(storage).check().is_err());
+
+        // account name of azblob is a credential too
+        let backend: StorageBackend = toml::from_str(
+            r#"
+            type = "azblob"
+            endpoint = "https://account.blob.core.windows.net"
+            container = "chain"
+            account_name = "secret:storage-keys/account_name"
+            account_key = "env:AZBLOB_ACCOUNT_KEY"
+            "#,
+        )
+        .unwrap();
+        assert!(backend.check().is_ok());
+        assert_eq!(backend.credentials()[0].0, "account_name");
+        let cloud_storage = backend.to_cloud_storage();
+        assert_eq!(cloud_storage.bucket, "chain");
+        assert_eq!(cloud_storage.access_key_id, "${STORAGE_ACCOUNT_NAME}");
+        assert_eq!(cloud_storage.secret_access_key, "${AZBLOB_ACCOUNT_KEY}");
+
+        assert!(StorageBackend::Fs {
+            root: String::new()
+        }
+        .check()
+        .is_err());
+        assert!(toml::from_str::<StorageBackend>("type = \"gcs\"").is_err());
+    }
+
+    #[test]
+    fn credential_test() {
+        let credential = Credential::from("secret:storage-keys/secret_access_key".to_string());
+        assert!(credential.check("secret_access_key").is_ok());
+        assert_eq!(
+            credential.render("secret_access_key"),
+            "${STORAGE_SECRET_ACCESS_KEY}"
+        );
+        assert_eq!(
+            String::from(credential),
+            "secret:storage-keys/secret_access_key"
+        );
+        assert!(Credential::from("secret:storage-keys".to_string())
+            .check("secret_access_key")
+            .is_err());
+
+        let credential = Credential::from("file:/run/secrets/access_key_id".to_string());
+        assert_eq!(
+            credential.render("access_key_id"),
+            "${file:/run/secrets/access_key_id}"
+        );
+        assert_eq!(credential.env_name("access_key_id"), None);
+        assert!(Credential::File("access_key_id".to_string())
+            .check("access_key_id")
+            .is_err());
     }
 }
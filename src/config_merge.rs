@@ -0,0 +1,219 @@
+// Copyright Rivtower Technologies LLC.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::constant::{GENESIS_BLOCK, RENDERED_SUFFIX, SYSTEM_CONFIG};
+use crate::error::Error;
+use std::collections::BTreeSet;
+use std::fs;
+use toml::{Table, Value};
+
+fn merge_table(
+    base: Option<&Table>,
+    local: &Table,
+    new: &Table,
+    path: &str,
+    force: bool,
+    conflicts: &mut Vec<String>,
+) -> Table {
+    let keys: BTreeSet<&String> = local
+        .keys()
+        .chain(new.keys())
+        .chain(base.into_iter().flat_map(|base| base.keys()))
+        .collect();
+
+    let mut merged = Table::new();
+    for key in keys {
+        let key_path = if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{path}.{key}")
+        };
+        let b = base.and_then(|base| base.get(key));
+        let l = local.get(key);
+        let n = new.get(key);
+        let value = match (l, n) {
+            // genesis must be same on all nodes, edits on node are discarded
+            _ if path.is_empty() && (key == GENESIS_BLOCK || key == SYSTEM_CONFIG) => n.cloned(),
+            (Some(Value::Table(l)), Some(Value::Table(n))) => Some(Value::Table(merge_table(
+                b.and_then(Value::as_table),
+                l,
+                n,
+                &key_path,
+                force,
+                conflicts,
+            ))),
+            // not edited on node, take new rendered
+            _ if l == b => n.cloned(),
+            // not changed by rendering, keep local edit
+            _ if n == b => l.cloned(),
+            _ if l == n => l.cloned(),
+            _ => {
+                conflicts.push(key_path);
+                if force {
+                    n.cloned()
+                } else {
+                    l.cloned()
+                }
+            }
+        };
+        if let Some(value) = value {
+            merged.insert(key.to_string(), value);
+        }
+    }
+    merged
+}
+
+/// three-way merge of config
+/// base is last rendered, local is current on node, new is newly rendered
+/// returns merged config and paths of conflicts
+/// conflicts take new value if force, otherwise keep local value
+/// genesis_block and system_config always take new value
+pub fn three_way_merge(
+    base: &Table,
+    local: &Table,
+    new: &Table,
+    force: bool,
+) -> (Table, Vec<String>) {
+    let mut conflicts = Vec::new();
+    let merged = merge_table(Some(base), local, new, "", force, &mut conflicts);
+    (merged, conflicts)
+}
+
+fn read_table(file_name: &str) -> Option<Table> {
+    fs::read_to_string(file_name)
+        .ok()
+        .and_then(|content| content.parse::<Table>().ok())
+}
+
+/// merge newly rendered config into config file on node
+/// last rendered config is kept in {config_file}.rendered as base of next merge
+pub fn merge_rendered(config_file: &str, render_file: &str, force: bool) -> Result<(), Error> {
+    let rendered_file = format!("{config_file}{RENDERED_SUFFIX}");
+    let new = read_table(render_file).unwrap();
+
+    match (read_table(&rendered_file), read_table(config_file)) {
+        // not edited on node, keep format of rendered config
+        (Some(base), Some(local)) if base == local => {
+            fs::copy(render_file, config_file).unwrap();
+        }
+        (Some(base), Some(local)) => {
+            let (merged, conflicts) = three_way_merge(&base, &local, &new, force);
+            if !conflicts.is_empty() {
+                if !force {
+                    println!(
+                        "edits on node conflict with rendered config: {conflicts:?}, use --force to overwrite them"
+                    );
+                    let _ = fs::remove_file(render_file);
+                    return Err(Error::ConfigMergeConflict);
+                }
+                println!("overwrite edits on node: {conflicts:?}");
+            }
+            fs::write(config_file, toml::to_string_pretty(&merged).unwrap()).unwrap();
+        }
+        // without last rendered config, edits on node can't be detected
+        _ => {
+            fs::copy(render_file, config_file).unwrap();
+        }
+    }
+
+    fs::rename(render_file, rendered_file).unwrap();
+    Ok(())
+}
+
+#[cfg(test)]
+mod config_merge_test {
+    use super::*;
+
+    #[test]
+    fn three_way_merge_test() {
+        let base: Table = r#"
+            [controller]
+            domain = "test-chain-node0"
+            is_danger = false
+            network_port = 50000
+            consensus_port = 50001
+            "#
+        .parse()
+        .unwrap();
+        let local: Table = r#"
+            [controller]
+            domain = "test-chain-node0"
+            is_danger = true
+            network_port = 51000
+            sync_block_num = 200
+            "#
+        .parse()
+        .unwrap();
+        let new: Table = r#"
+            [controller]
+            domain = "test-chain-node1"
+            is_danger = false
+            network_port = 52000
+            consensus_port = 50001
+            "#
+        .parse()
+        .unwrap();
+
+        let (merged, conflicts) = three_way_merge(&base, &local, &new, false);
+        assert_eq!(conflicts, vec!["controller.network_port".to_string()]);
+        let controller = merged["controller"].as_table().unwrap();
+        assert_eq!(controller["domain"].as_str(), Some("test-chain-node1"));
+        assert_eq!(controller["is_danger"].as_bool(), Some(true));
+        assert_eq!(controller["network_port"].as_integer(), Some(51000));
+        assert_eq!(controller["sync_block_num"].as_integer(), Some(200));
+        assert!(!controller.contains_key("consensus_port"));
+
+        let (merged, _) = three_way_merge(&base, &local, &new, true);
+        let controller = merged["controller"].as_table().unwrap();
+        assert_eq!(controller["network_port"].as_integer(), Some(52000));
+    }
+    #[test]
+    fn genesis_merge_test() {
+        let base: Table = r#"
+            [genesis_block]
+            prevhash = "0x00"
+            timestamp = 1000
+
+            [system_config]
+            block_interval = 3
+            "#
+        .parse()
+        .unwrap();
+        let local: Table = r#"
+            [genesis_block]
+            prevhash = "0x00"
+            timestamp = 2000
+
+            [system_config]
+            block_interval = 6
+            block_limit = 200
+            "#
+        .parse()
+        .unwrap();
+
+        // edits on node are discarded even not changed by rendering
+        let (merged, conflicts) = three_way_merge(&base, &local, &base, false);
+        assert!(conflicts.is_empty());
+        assert_eq!(merged, base);
+
+        let mut new = base.clone();
+        new["system_config"]
+            .as_table_mut()
+            .unwrap()
+            .insert("block_interval".to_string(), Value::Integer(9));
+        let (merged, conflicts) = three_way_merge(&base, &local, &new, false);
+        assert!(conflicts.is_empty());
+        assert_eq!(merged, new);
+    }
+}
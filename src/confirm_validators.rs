@@ -0,0 +1,69 @@
+// Copyright Rivtower Technologies LLC.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::audit::{audit, chain_config_hash};
+use crate::config::chain_config::ConfigStage;
+use crate::constant::CHAIN_CONFIG_FILE;
+use crate::error::Error;
+use crate::util::{read_chain_config, remove_0x, write_toml};
+use clap::Parser;
+
+/// A subcommand for run
+#[derive(Parser, Debug, Clone)]
+pub struct ConfirmValidatorsOpts {
+    /// set chain name
+    #[clap(long = "chain-name", default_value = "test-chain")]
+    pub chain_name: String,
+    /// set config file directory, default means current directory
+    #[clap(long = "config-dir", default_value = ".")]
+    pub config_dir: String,
+    /// hash of proposed transaction which has been confirmed on chain
+    #[clap(long = "tx-hash")]
+    pub tx_hash: String,
+}
+
+/// execute confirm validators
+/// pending validators proposed by propose-validators take effect
+pub fn execute_confirm_validators(opts: ConfirmValidatorsOpts) -> Result<(), Error> {
+    let before_hash = chain_config_hash(&opts.config_dir, &opts.chain_name);
+    // load chain_config
+    let file_name = format!(
+        "{}/{}/{}",
+        &opts.config_dir, &opts.chain_name, CHAIN_CONFIG_FILE
+    );
+    let mut chain_config = read_chain_config(&file_name).unwrap();
+    chain_config.check_genesis_hash()?;
+
+    if chain_config.stage != ConfigStage::Finalize {
+        return Err(Error::InvalidStage);
+    }
+
+    let tx_hash = remove_0x(&opts.tx_hash).to_lowercase();
+    if let Err(e) = chain_config.confirm_pending_validators(&tx_hash) {
+        println!("no pending validators proposed by tx 0x{tx_hash}");
+        return Err(e);
+    }
+
+    // store chain_config
+    write_toml(&chain_config, file_name);
+
+    audit(
+        &opts.config_dir,
+        &opts.chain_name,
+        "confirm-validators",
+        &before_hash,
+    );
+
+    Ok(())
+}
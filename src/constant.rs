@@ -25,6 +25,8 @@ pub const NETWORK_ZENOH: &str = "network_zenoh";
 pub const STORAGE_OPENDAL: &str = "storage_opendal";
 pub const STORAGE: &str = "storage";
 pub const EXECUTOR_EVM: &str = "executor_evm";
+pub const EXECUTOR_CHAINCODE: &str = "executor_chaincode";
+pub const DEFAULT_CHAINCODE_PORT: u16 = 7052;
 pub const EXECUTOR: &str = "executor";
 pub const PRE_HASH: &str = "0x0000000000000000000000000000000000000000000000000000000000000000";
 pub const DEFAULT_BLOCK_INTERVAL: u32 = 3;
@@ -41,3 +43,16 @@ pub const PRIVATE_KEY: &str = "private_key";
 pub const VALIDATOR_ADDRESS: &str = "validator_address";
 pub const NODE_ADDRESS: &str = "node_address";
 pub const DEFAULT_QUOTA_LIMIT: u64 = 1073741824;
+pub const VALIDATORS_PROPOSAL_FILE: &str = "validators_proposal.toml";
+// lock ids of controller: 1000 version, 1001 chain_id, 1002 admin, 1003 block_interval
+pub const LOCK_ID_VALIDATORS: u64 = 1004;
+pub const APPROVAL_DIR: &str = "approvals";
+pub const JOIN_REQUEST_FILE: &str = "join_request.toml";
+pub const AUDIT_LOG_FILE: &str = "audit_log.toml";
+pub const DEFAULT_INTERNAL_PORT: u16 = 40000;
+pub const PORT_ALLOCATIONS_FILE: &str = "port_allocations.toml";
+pub const OVERRIDES_FILE: &str = "overrides.toml";
+pub const RENDERED_SUFFIX: &str = ".rendered";
+pub const UPDATE_YAML_OPTS_FILE: &str = "update_yaml_opts.toml";
+/// schema version of chain_config.toml and node_config.toml
+pub const SCHEMA_VERSION: u64 = 2;
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::audit::{audit, chain_config_hash};
 use crate::constant::{CA_CERT_DIR, CERT_PEM, KEY_PEM};
 use crate::error::Error;
 use crate::util::{ca_cert, write_file};
@@ -30,6 +31,7 @@ pub struct CreateCAOpts {
 
 /// execute create ca
 pub fn execute_create_ca(opts: CreateCAOpts) -> Result<(String, String), Error> {
+    let before_hash = chain_config_hash(&opts.config_dir, &opts.chain_name);
     let (ca_cert_pem, ca_key_pem) = ca_cert();
 
     let path = format!(
@@ -44,5 +46,12 @@ pub fn execute_create_ca(opts: CreateCAOpts) -> Result<(String, String), Error>
     );
     write_file(ca_key_pem.as_bytes(), path);
 
+    audit(
+        &opts.config_dir,
+        &opts.chain_name,
+        "create-ca",
+        &before_hash,
+    );
+
     Ok((ca_cert_pem, ca_key_pem))
 }
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::audit::{audit, chain_config_hash};
 use crate::constant::{CERTS_DIR, CSR_PEM, KEY_PEM};
 use crate::error::Error;
 use crate::util::{create_csr, write_file};
@@ -34,6 +35,7 @@ pub struct CreateCSROpts {
 
 /// execute create csr
 pub fn execute_create_csr(opts: CreateCSROpts) -> Result<(String, String), Error> {
+    let before_hash = chain_config_hash(&opts.config_dir, &opts.chain_name);
     // gen csr and key_pem of node by domain
     let real_domain = format!("{}-{}", &opts.chain_name, &opts.domain);
     let (csr_pem, key_pem) = create_csr(&real_domain);
@@ -51,5 +53,12 @@ pub fn execute_create_csr(opts: CreateCSROpts) -> Result<(String, String), Error
     let key_pem_path = format!("{}/{}", &path, KEY_PEM);
     write_file(key_pem.as_bytes(), key_pem_path);
 
+    audit(
+        &opts.config_dir,
+        &opts.chain_name,
+        "create-csr",
+        &before_hash,
+    );
+
     Ok((csr_pem, key_pem))
 }
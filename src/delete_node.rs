@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::audit::{audit, chain_config_hash};
 use crate::config::chain_config::ConfigStage;
 use crate::constant::{ACCOUNT_DIR, CERTS_DIR, CHAIN_CONFIG_FILE, NODE_CONFIG_FILE};
 use crate::error::Error;
@@ -37,12 +38,14 @@ pub struct DeleteNodeOpts {
 }
 
 pub fn execute_delete_node(opts: DeleteNodeOpts) -> Result<(), Error> {
+    let before_hash = chain_config_hash(&opts.config_dir, &opts.chain_name);
     // load chain_config
     let file_name = format!(
         "{}/{}/{}",
         &opts.config_dir, &opts.chain_name, CHAIN_CONFIG_FILE
     );
     let mut chain_config = read_chain_config(&file_name).unwrap();
+    chain_config.check_genesis_hash()?;
 
     if chain_config.stage == ConfigStage::Init {
         return Err(Error::InvalidStage);
@@ -73,6 +76,13 @@ pub fn execute_delete_node(opts: DeleteNodeOpts) -> Result<(), Error> {
     // store chain_config
     write_toml(&chain_config, file_name);
 
+    audit(
+        &opts.config_dir,
+        &opts.chain_name,
+        "delete-node",
+        &before_hash,
+    );
+
     Ok(())
 }
 
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::audit::{audit, chain_config_hash};
 use crate::config::chain_config::ConfigStage;
 use crate::constant::CHAIN_CONFIG_FILE;
 use crate::error::Error;
@@ -34,12 +35,14 @@ pub struct DeleteValidatorOpts {
 
 /// execute delete validator
 pub fn execute_delete_validator(opts: DeleteValidatorOpts) -> Result<(), Error> {
+    let before_hash = chain_config_hash(&opts.config_dir, &opts.chain_name);
     // load chain_config
     let file_name = format!(
         "{}/{}/{}",
         &opts.config_dir, &opts.chain_name, CHAIN_CONFIG_FILE
     );
     let mut chain_config = read_chain_config(&file_name).unwrap();
+    chain_config.check_genesis_hash()?;
 
     if chain_config.stage != ConfigStage::Public {
         return Err(Error::InvalidStage);
@@ -70,5 +73,12 @@ pub fn execute_delete_validator(opts: DeleteValidatorOpts) -> Result<(), Error>
     // store chain_config
     write_toml(&chain_config, file_name);
 
+    audit(
+        &opts.config_dir,
+        &opts.chain_name,
+        "delete-validator",
+        &before_hash,
+    );
+
     Ok(())
 }
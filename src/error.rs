@@ -18,4 +18,22 @@ pub enum Error {
     DupChainName,
     InvalidStage,
     FileNoFound,
+    GenesisHashMismatch,
+    GenesisHashMissing,
+    InsufficientApprovals,
+    JoinRequestConflict,
+    InvalidNodeAddress,
+    InvalidTopology,
+    InvalidNetworkConfig,
+    InvalidOverrides,
+    ConfigMergeConflict,
+    InvalidLogConfig,
+    InvalidMicroService,
+    IncompatibleImages,
+    UnsupportedSchemaVersion,
+    InvalidConsensusConfig,
+    InvalidStorageConfig,
+    InvalidExecutorConfig,
+    ValidatorsNotProposed,
+    InvalidValidators,
 }
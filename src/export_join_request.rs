@@ -0,0 +1,223 @@
+// Copyright Rivtower Technologies LLC.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::config::chain_config::{NodeNetworkAddress, NodeRole};
+use crate::constant::{
+    ACCOUNT_DIR, CERTS_DIR, CSR_PEM, JOIN_REQUEST_FILE, PRIVATE_KEY, VALIDATOR_ADDRESS,
+};
+use crate::error::Error;
+use crate::util::{
+    crypto_hash, parse_node, read_file, recover_address, remove_0x, sign_message, write_toml,
+};
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+
+/// A subcommand for run
+#[derive(Parser, Debug, Clone)]
+pub struct ExportJoinRequestOpts {
+    /// set chain name
+    #[clap(long = "chain-name", default_value = "test-chain")]
+    pub chain_name: String,
+    /// set config file directory, default means current directory
+    #[clap(long = "config-dir", default_value = ".")]
+    pub config_dir: String,
+    /// node network address looks like localhost:40002:node2:k8s_cluster_name:namespace
+    /// k8s_cluster_name is optional, none means not k8s env.
+    /// namespace is optional, none means default namespace.
+    /// protocol prefix is optional, quic/tls/tcp, none means quic, like tcp/localhost:40000:node0
+    #[clap(long = "node")]
+    pub node: String,
+    /// node account created by new-account
+    #[clap(long = "account")]
+    pub account: String,
+    /// sign join request with node account
+    #[clap(long = "sign")]
+    pub sign: bool,
+    /// set join request file name, default is $(domain)_join_request.toml
+    #[clap(long = "output")]
+    pub output: Option<String>,
+}
+
+/// information contributed by a participant in decentralized mode
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JoinRequest {
+    pub node: NodeNetworkAddress,
+    pub node_address: String,
+    pub validator_address: String,
+    pub csr: String,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+impl JoinRequest {
+    fn message(&self) -> Vec<u8> {
+        let content = JoinRequest {
+            signature: None,
+            ..self.clone()
+        };
+        crypto_hash(toml::to_string(&content).unwrap().as_bytes())
+    }
+
+    /// signed request must be signed by node_address,
+    /// unsigned request is valid only if node is not a validator
+    pub fn verify(&self) -> bool {
+        match &self.signature {
+            None => self.node.role != NodeRole::Validator,
+            Some(signature) => {
+                let signature = match hex::decode(signature) {
+                    Ok(signature) => signature,
+                    Err(_) => return false,
+                };
+                match recover_address(&self.message(), &signature) {
+                    Some(address) => hex::encode(address) == self.node_address,
+                    None => false,
+                }
+            }
+        }
+    }
+}
+
+/// execute export join request
+pub fn execute_export_join_request(opts: ExportJoinRequestOpts) -> Result<JoinRequest, Error> {
+    // parse node network info
+    let node = parse_node(&opts.node)?;
+
+    let account = remove_0x(&opts.account);
+    let account_dir = format!(
+        "{}/{}/{}/{}",
+        &opts.config_dir, &opts.chain_name, ACCOUNT_DIR, account
+    );
+    let validator_address = read_file(format!("{account_dir}/{VALIDATOR_ADDRESS}"))
+        .unwrap()
+        .trim()
+        .to_string();
+
+    // csr created by create-csr
+    let csr = read_file(format!(
+        "{}/{}/{}/{}/{}",
+        &opts.config_dir, &opts.chain_name, CERTS_DIR, &node.domain, CSR_PEM
+    ))
+    .unwrap();
+
+    let mut join_request = JoinRequest {
+        node,
+        node_address: account.to_string(),
+        validator_address,
+        csr,
+        signature: None,
+    };
+
+    if opts.sign {
+        let private_key = hex::decode(remove_0x(
+            read_file(format!("{account_dir}/{PRIVATE_KEY}"))
+                .unwrap()
+                .trim(),
+        ))
+        .expect("invalid private key");
+        let signature = sign_message(&private_key, &join_request.message());
+        join_request.signature = Some(hex::encode(signature));
+    }
+
+    let output = match &opts.output {
+        Some(output) => output.clone(),
+        None => format!(
+            "{}/{}/{}_{}",
+            &opts.config_dir, &opts.chain_name, &join_request.node.domain, JOIN_REQUEST_FILE
+        ),
+    };
+    write_toml(&join_request, &output);
+    println!("join request: {output}");
+
+    Ok(join_request)
+}
+
+#[cfg(test)]
+mod export_join_request_test {
+    use super::*;
+    use crate::create_csr::{execute_create_csr, CreateCSROpts};
+    use crate::init_chain::{execute_init_chain, InitChainOpts};
+    use crate::init_chain_config::{execute_init_chain_config, InitChainConfigOpts};
+    use crate::new_account::{execute_new_account, NewAccountOpts};
+    use crate::util::read_file;
+    use std::fs;
+
+    #[test]
+    fn export_test() {
+        let config_dir = "/tmp/test-export-join-request";
+        let _ = fs::remove_dir_all(config_dir);
+        let args = ["--config-dir", config_dir];
+        execute_init_chain(InitChainOpts::parse_from(
+            ["init-chain"].iter().chain(&args),
+        ))
+        .unwrap();
+        execute_init_chain_config(InitChainConfigOpts::parse_from(
+            ["init-chain-config"].iter().chain(&args),
+        ))
+        .unwrap();
+        let (account, validator) = execute_new_account(NewAccountOpts::parse_from(
+            ["new-account"].iter().chain(&args),
+        ))
+        .unwrap();
+        let (csr, _) = execute_create_csr(CreateCSROpts::parse_from(
+            ["create-csr", "--domain", "node0"].iter().chain(&args),
+        ))
+        .unwrap();
+
+        let export = |sign: bool| {
+            let mut opts = ExportJoinRequestOpts::parse_from(
+                [
+                    "export-join-request",
+                    "--node",
+                    "tcp/localhost:40000:node0",
+                    "--account",
+                    &account,
+                ]
+                .iter()
+                .chain(&args),
+            );
+            opts.sign = sign;
+            execute_export_join_request(opts).unwrap()
+        };
+
+        let join_request = export(false);
+        assert_eq!(join_request.node.domain, "node0");
+        assert_eq!(join_request.validator_address, validator);
+        assert_eq!(join_request.csr, csr);
+        assert!(join_request.signature.is_none());
+        // validator must sign join request
+        assert!(!join_request.verify());
+        let mut observer = join_request.clone();
+        observer.node.role = NodeRole::Observer;
+        assert!(observer.verify());
+
+        let join_request = export(true);
+        assert!(join_request.verify());
+        let exported: JoinRequest = toml::from_str(
+            &read_file(format!("{config_dir}/test-chain/node0_join_request.toml")).unwrap(),
+        )
+        .unwrap();
+        assert!(exported.verify());
+
+        // signed content can't be modified
+        let mut tampered = exported.clone();
+        tampered.node.port = 40001;
+        assert!(!tampered.verify());
+        let mut tampered = exported;
+        tampered.node_address = "aa".repeat(20);
+        assert!(!tampered.verify());
+
+        fs::remove_dir_all(config_dir).unwrap();
+    }
+}
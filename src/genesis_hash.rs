@@ -0,0 +1,55 @@
+// Copyright Rivtower Technologies LLC.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::config::chain_config::ConfigStage;
+use crate::constant::CHAIN_CONFIG_FILE;
+use crate::error::Error;
+use crate::util::read_chain_config;
+use clap::Parser;
+
+/// A subcommand for run
+#[derive(Parser, Debug, Clone)]
+pub struct GenesisHashOpts {
+    /// set chain name
+    #[clap(long = "chain-name", default_value = "test-chain")]
+    pub chain_name: String,
+    /// set config file directory, default means current directory
+    #[clap(long = "config-dir", default_value = ".")]
+    pub config_dir: String,
+}
+
+/// print genesis fingerprint to compare with other participants
+pub fn execute_genesis_hash(opts: GenesisHashOpts) -> Result<String, Error> {
+    // load chain_config
+    let file_name = format!(
+        "{}/{}/{}",
+        &opts.config_dir, &opts.chain_name, CHAIN_CONFIG_FILE
+    );
+    let chain_config = read_chain_config(file_name).unwrap();
+
+    if chain_config.stage != ConfigStage::Finalize {
+        return Err(Error::InvalidStage);
+    }
+
+    chain_config.check_genesis_hash()?;
+
+    // chain finalized by old version has no stored fingerprint
+    let genesis_hash = match &chain_config.genesis_hash {
+        Some(genesis_hash) => genesis_hash.clone(),
+        None => chain_config.compute_genesis_hash(),
+    };
+    println!("genesis_hash: {genesis_hash}");
+
+    Ok(genesis_hash)
+}
@@ -0,0 +1,61 @@
+// Copyright Rivtower Technologies LLC.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::audit::{read_audit_log, AuditRecord};
+use crate::error::Error;
+use clap::Parser;
+use std::path::Path;
+
+/// A subcommand for run
+#[derive(Parser, Debug, Clone)]
+pub struct HistoryOpts {
+    /// set chain name
+    #[clap(long = "chain-name", default_value = "test-chain")]
+    pub chain_name: String,
+    /// set config file directory, default means current directory
+    #[clap(long = "config-dir", default_value = ".")]
+    pub config_dir: String,
+    /// only show records of this command, such as set-admin
+    #[clap(long = "command")]
+    pub command: Option<String>,
+}
+
+/// execute history
+/// print audit log of chain in order
+pub fn execute_history(opts: HistoryOpts) -> Result<Vec<AuditRecord>, Error> {
+    let chain_dir = format!("{}/{}", &opts.config_dir, &opts.chain_name);
+    if !Path::new(&chain_dir).exists() {
+        return Err(Error::FileNoFound);
+    }
+
+    let records: Vec<AuditRecord> = read_audit_log(&opts.config_dir, &opts.chain_name)
+        .record
+        .into_iter()
+        .filter(|r| opts.command.is_none() || opts.command.as_ref() == Some(&r.command))
+        .collect();
+
+    for record in &records {
+        println!(
+            "{} {} {} {} -> {}",
+            record.timestamp,
+            &record.user,
+            &record.command,
+            &record.before_hash,
+            &record.after_hash
+        );
+        println!("    {}", record.args.join(" "));
+    }
+
+    Ok(records)
+}
@@ -16,6 +16,7 @@ use std::fs;
 
 use clap::Parser;
 
+use crate::audit::{audit, chain_config_hash};
 use crate::{
     constant::{
         ACCOUNT_DIR, CHAIN_CONFIG_FILE, CONSENSUS_OVERLORD, NODE_ADDRESS, PRIVATE_KEY,
@@ -43,12 +44,14 @@ pub struct ImportAccountOpts {
 }
 
 pub fn execute_import_account(opts: ImportAccountOpts) -> Result<(String, String), Error> {
+    let before_hash = chain_config_hash(&opts.config_dir, &opts.chain_name);
     // load chain_config
     let file_name = format!(
         "{}/{}/{}",
         &opts.config_dir, &opts.chain_name, CHAIN_CONFIG_FILE
     );
     let chain_config = read_chain_config(file_name).unwrap();
+    chain_config.check_genesis_hash()?;
 
     let private_key = {
         let s = crate::util::remove_0x(&opts.privkey);
@@ -96,5 +99,12 @@ pub fn execute_import_account(opts: ImportAccountOpts) -> Result<(String, String
     // output node_address and validator_address
     println!("node_address: {address} validator_address: {validator_address}");
 
+    audit(
+        &opts.config_dir,
+        &opts.chain_name,
+        "import-account",
+        &before_hash,
+    );
+
     Ok((address, validator_address))
 }
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::audit::{audit, chain_config_hash};
 use crate::constant::{CA_CERT_DIR, CERT_PEM, KEY_PEM};
 use crate::error::Error;
 use crate::util::read_file;
@@ -38,6 +39,7 @@ pub struct ImportCAOpts {
 
 /// execute import ca
 pub fn execute_import_ca(opts: ImportCAOpts) -> Result<(String, String), Error> {
+    let before_hash = chain_config_hash(&opts.config_dir, &opts.chain_name);
     if !Path::new(&opts.ca_cert_path).exists() {
         return Err(Error::FileNoFound);
     }
@@ -63,5 +65,12 @@ pub fn execute_import_ca(opts: ImportCAOpts) -> Result<(String, String), Error>
     let ca_cert_pem = read_file(cert_path).unwrap();
     let ca_key_pem = read_file(key_path).unwrap();
 
+    audit(
+        &opts.config_dir,
+        &opts.chain_name,
+        "import-ca",
+        &before_hash,
+    );
+
     Ok((ca_cert_pem, ca_key_pem))
 }
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::audit::{audit, chain_config_hash};
 use crate::constant::{CERTS_DIR, CERT_PEM, KEY_PEM};
 use crate::error::Error;
 use crate::util::read_file;
@@ -41,6 +42,7 @@ pub struct ImportCertOpts {
 
 /// execute import cert
 pub fn execute_import_cert(opts: ImportCertOpts) -> Result<(String, String), Error> {
+    let before_hash = chain_config_hash(&opts.config_dir, &opts.chain_name);
     if !Path::new(&opts.cert_path).exists() {
         return Err(Error::FileNoFound);
     }
@@ -65,5 +67,12 @@ pub fn execute_import_cert(opts: ImportCertOpts) -> Result<(String, String), Err
     let cert_pem = read_file(cert_pem_path).unwrap();
     let key_pem = read_file(key_pem_path).unwrap();
 
+    audit(
+        &opts.config_dir,
+        &opts.chain_name,
+        "import-cert",
+        &before_hash,
+    );
+
     Ok((cert_pem, key_pem))
 }
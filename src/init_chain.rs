@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::audit::{audit, chain_config_hash};
 use crate::constant::{ACCOUNT_DIR, CA_CERT_DIR, CERTS_DIR, KEY_PEM};
 use crate::error::Error;
 use crate::util::{touch_file, write_file};
@@ -41,6 +42,7 @@ pub struct InitChainOpts {
 /// --------  .gitkeep
 /// ------  .gitignore
 pub fn execute_init_chain(opts: InitChainOpts) -> Result<(), Error> {
+    let before_hash = chain_config_hash(&opts.config_dir, &opts.chain_name);
     let chain_path = format!("{}/{}", &opts.config_dir, &opts.chain_name);
     if Path::new(&chain_path).exists() {
         return Err(Error::DupChainName);
@@ -65,5 +67,12 @@ pub fn execute_init_chain(opts: InitChainOpts) -> Result<(), Error> {
     let git_ignore_content =
         format!("{ACCOUNT_DIR}/*/\n{CA_CERT_DIR}/{KEY_PEM}\n{CERTS_DIR}/*/{KEY_PEM}\n");
     write_file(git_ignore_content.as_bytes(), git_ignore_path);
+    audit(
+        &opts.config_dir,
+        &opts.chain_name,
+        "init-chain",
+        &before_hash,
+    );
+
     Ok(())
 }
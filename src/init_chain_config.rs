@@ -12,13 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::audit::{audit, chain_config_hash};
 use crate::config::chain_config::ConfigStage;
-use crate::config::chain_config::{ChainConfigBuilder, MicroService, MicroServiceBuilder};
+use crate::config::chain_config::{
+    ChainConfigBuilder, MicroService, MicroServiceBuilder, ServiceRole,
+};
 use crate::config::controller::{GenesisBlockBuilder, SystemConfigBuilder};
 use crate::constant::CHAIN_CONFIG_FILE;
 use crate::error::Error;
 use crate::util::{read_chain_config, sm3_hash, unix_now, write_toml};
 use clap::Parser;
+use std::num::NonZeroUsize;
 use std::path::Path;
 
 /// A subcommand for run
@@ -66,7 +70,7 @@ pub struct InitChainConfigOpts {
     /// set consensus micro service image tag
     #[clap(long = "consensus_tag", default_value = "latest")]
     pub consensus_tag: String,
-    /// set executor micro service image name (executor_evm)
+    /// set executor micro service image name (executor_evm/executor_chaincode)
     #[clap(long = "executor_image", default_value = "executor_evm")]
     pub executor_image: String,
     /// set executor micro service image tag
@@ -84,6 +88,12 @@ pub struct InitChainConfigOpts {
     /// set controller micro service image tag
     #[clap(long = "controller_tag", default_value = "latest")]
     pub controller_tag: String,
+    /// set network listen port of nodes in k8s
+    #[clap(long = "internal-port", default_value = "40000")]
+    pub internal_port: u16,
+    /// set approvals of validators required by finalize, default is majority of validators
+    #[clap(long = "approval-quorum")]
+    pub approval_quorum: Option<NonZeroUsize>,
 }
 
 /// init chain config
@@ -91,6 +101,7 @@ pub struct InitChainConfigOpts {
 /// --  $(chain_name)
 /// ------  chain_config.toml
 pub fn execute_init_chain_config(opts: InitChainConfigOpts) -> Result<(), Error> {
+    let before_hash = chain_config_hash(&opts.config_dir, &opts.chain_name);
     let file_name = format!(
         "{}/{}/{}",
         &opts.config_dir, &opts.chain_name, CHAIN_CONFIG_FILE
@@ -98,6 +109,7 @@ pub fn execute_init_chain_config(opts: InitChainConfigOpts) -> Result<(), Error>
 
     if Path::new(&file_name).exists() {
         let chain_config = read_chain_config(&file_name).unwrap();
+        chain_config.check_genesis_hash()?;
         // rewrite chain config only when stage is Init
         if chain_config.stage != ConfigStage::Init {
             return Err(Error::InvalidStage);
@@ -121,22 +133,27 @@ pub fn execute_init_chain_config(opts: InitChainConfigOpts) -> Result<(), Error>
     let network_micro_service = MicroServiceBuilder::default()
         .image(opts.network_image)
         .tag(opts.network_tag)
+        .role(ServiceRole::Network)
         .build();
     let consensus_micro_service = MicroServiceBuilder::default()
         .image(opts.consensus_image)
         .tag(opts.consensus_tag)
+        .role(ServiceRole::Consensus)
         .build();
     let executor_micro_service = MicroServiceBuilder::default()
         .image(opts.executor_image)
         .tag(opts.executor_tag)
+        .role(ServiceRole::Executor)
         .build();
     let storage_micro_service = MicroServiceBuilder::default()
         .image(opts.storage_image)
         .tag(opts.storage_tag)
+        .role(ServiceRole::Storage)
         .build();
     let controller_micro_service = MicroServiceBuilder::default()
         .image(opts.controller_image)
         .tag(opts.controller_tag)
+        .role(ServiceRole::Controller)
         .build();
 
     let micro_service_list: Vec<MicroService> = vec![
@@ -166,9 +183,18 @@ pub fn execute_init_chain_config(opts: InitChainConfigOpts) -> Result<(), Error>
         .system_config(system_config)
         .genesis_block(genesis_block)
         .micro_service_list(micro_service_list)
+        .internal_port(opts.internal_port)
+        .approval_quorum(opts.approval_quorum.map(NonZeroUsize::get))
         .build();
 
     write_toml(chain_config, file_name);
 
+    audit(
+        &opts.config_dir,
+        &opts.chain_name,
+        "init-chain-config",
+        &before_hash,
+    );
+
     Ok(())
 }
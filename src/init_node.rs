@@ -12,16 +12,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::audit::{audit, chain_config_hash};
 use crate::config::chain_config::ConfigStage;
+use crate::config::executor_chaincode::ChaincodeTuning;
+use crate::config::log_config::TracingConfig;
 use crate::config::node_config::{
-    CloudStorageBuilder, ExportConfig, GrpcPortsBuilder, MetricsPortsBuilder, NodeConfigBuilder,
+    ExportConfig, GrpcPortsBuilder, MetricsPortsBuilder, NodeConfigBuilder, ServiceLogConfig,
 };
+use crate::config::storage_opendal::{AzblobStorage, ObjectStorage, StorageBackend};
 use crate::constant::{
     ACCOUNT_DIR, CA_CERT_DIR, CERTS_DIR, CERT_PEM, CHAIN_CONFIG_FILE, NODE_CONFIG_FILE,
 };
 use crate::error::Error;
 use crate::util::{copy_dir_all, read_chain_config, remove_0x, write_toml};
 use clap::Parser;
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 
@@ -58,9 +63,36 @@ pub struct InitNodeOpts {
     /// log file path
     #[clap(long = "log-file-path")]
     pub log_file_path: Option<String>,
-    /// jaeger agent endpoint
+    /// jaeger agent endpoint, deprecated, use tracing-endpoint instead
     #[clap(long = "jaeger-agent-endpoint")]
     pub jaeger_agent_endpoint: Option<String>,
+    /// tracing exporter: jaeger-agent/otlp-grpc/otlp-http
+    #[clap(long = "tracing-exporter", default_value = "otlp-grpc")]
+    pub tracing_exporter: String,
+    /// tracing endpoint, tracing is disabled if not set
+    #[clap(long = "tracing-endpoint")]
+    pub tracing_endpoint: Option<String>,
+    /// ratio of traces sampled, in [0, 1]
+    #[clap(long = "tracing-sampling-ratio", default_value = "1.0")]
+    pub tracing_sampling_ratio: f64,
+    /// resource attributes of tracing, like key1=value1,key2=value2
+    #[clap(long = "tracing-resource-attributes", default_value = "")]
+    pub tracing_resource_attributes: String,
+    /// log level of a micro service, like consensus=trace, can be repeated
+    #[clap(long = "service-log-level")]
+    pub service_log_level: Vec<String>,
+    /// log filter of a micro service in env-filter syntax, like consensus=consensus=trace,info
+    #[clap(long = "service-log-filter")]
+    pub service_log_filter: Vec<String>,
+    /// log file path of a micro service, like consensus=./logs/consensus
+    #[clap(long = "service-log-file-path")]
+    pub service_log_file_path: Vec<String>,
+    /// jaeger agent endpoint of a micro service, like consensus=localhost:6831
+    #[clap(long = "service-jaeger-agent-endpoint")]
+    pub service_jaeger_agent_endpoint: Vec<String>,
+    /// port listened by executor_chaincode for chaincode to connect, default is 7052
+    #[clap(long = "chaincode-port")]
+    pub chaincode_port: Option<u16>,
     /// account of node
     #[clap(long = "account")]
     pub account: String,
@@ -88,25 +120,25 @@ pub struct InitNodeOpts {
     /// enable tx persistence
     #[clap(long = "enable-tx-persistence")]
     pub enable_tx_persistence: bool,
-    /// cloud_storage.access_key_id
+    /// storage_backend.access_key_id, account_name of azblob, env:NAME, file:PATH or secret:NAME/KEY refers to credential instead of plain text
     #[clap(long = "access-key-id", default_value = "")]
     pub access_key_id: String,
-    /// cloud_storage.secret_access_key
+    /// storage_backend.secret_access_key, account_key of azblob, env:NAME, file:PATH or secret:NAME/KEY refers to credential instead of plain text
     #[clap(long = "secret-access-key", default_value = "")]
     pub secret_access_key: String,
-    /// cloud_storage.endpoint
+    /// storage_backend.endpoint
     #[clap(long = "s3-endpoint", default_value = "")]
     pub s3_endpoint: String,
-    /// cloud_storage.bucket
+    /// storage_backend.bucket, container of azblob
     #[clap(long = "s3-bucket", default_value = "")]
     pub s3_bucket: String,
-    /// cloud_storage.service_type: s3/oss(aliyun)/obs(huawei)/cos(tencent)/azblob(azure)
+    /// storage_backend.type: fs/s3/oss(aliyun)/obs(huawei)/cos(tencent)/azblob(azure), empty means no cloud storage
     #[clap(long = "service-type", default_value = "")]
     pub service_type: String,
-    /// cloud_storage.root
+    /// storage_backend.root
     #[clap(long = "s3-root", default_value = "")]
     pub s3_root: String,
-    /// cloud_storage.region
+    /// storage_backend.region
     #[clap(long = "s3-region", default_value = "")]
     pub s3_region: String,
     /// exporter.base_path
@@ -114,8 +146,104 @@ pub struct InitNodeOpts {
     pub exporter_path: String,
 }
 
+// option of a micro service is SERVICE=VALUE
+fn split_service_option(option: &str) -> Result<(String, String), Error> {
+    option
+        .split_once('=')
+        .map(|(service, value)| (service.to_string(), value.to_string()))
+        .ok_or_else(|| {
+            println!("invalid service option: {option}, should be SERVICE=VALUE");
+            Error::InvalidLogConfig
+        })
+}
+
+fn parse_service_log(opts: &InitNodeOpts) -> Result<BTreeMap<String, ServiceLogConfig>, Error> {
+    let mut service_log: BTreeMap<String, ServiceLogConfig> = BTreeMap::new();
+    for option in &opts.service_log_level {
+        let (service, value) = split_service_option(option)?;
+        service_log.entry(service).or_default().max_level = Some(value);
+    }
+    for option in &opts.service_log_filter {
+        let (service, value) = split_service_option(option)?;
+        service_log.entry(service).or_default().filter = Some(value);
+    }
+    for option in &opts.service_log_file_path {
+        let (service, value) = split_service_option(option)?;
+        service_log.entry(service).or_default().rolling_file_path = Some(value);
+    }
+    for option in &opts.service_jaeger_agent_endpoint {
+        let (service, value) = split_service_option(option)?;
+        service_log.entry(service).or_default().agent_endpoint = Some(value);
+    }
+    Ok(service_log)
+}
+
+fn parse_tracing(opts: &InitNodeOpts) -> Result<Option<TracingConfig>, Error> {
+    let Some(endpoint) = opts.tracing_endpoint.clone() else {
+        return Ok(None);
+    };
+    let exporter = opts.tracing_exporter.parse().map_err(|e| {
+        println!("{e}");
+        Error::InvalidLogConfig
+    })?;
+    let mut tracing = TracingConfig::new(exporter, endpoint);
+    tracing.sampling_ratio = opts.tracing_sampling_ratio;
+    for attribute in opts
+        .tracing_resource_attributes
+        .split(',')
+        .filter(|attribute| !attribute.is_empty())
+    {
+        let (key, value) = attribute.split_once('=').ok_or_else(|| {
+            println!("invalid resource attribute: {attribute}, should be key=value");
+            Error::InvalidLogConfig
+        })?;
+        tracing
+            .resource_attributes
+            .insert(key.to_string(), value.to_string());
+    }
+    Ok(Some(tracing))
+}
+
+fn parse_storage_backend(opts: &InitNodeOpts) -> Result<Option<StorageBackend>, Error> {
+    let object_storage = || ObjectStorage {
+        endpoint: opts.s3_endpoint.clone(),
+        bucket: opts.s3_bucket.clone(),
+        root: opts.s3_root.clone(),
+        region: opts.s3_region.clone(),
+        access_key_id: opts.access_key_id.clone().into(),
+        secret_access_key: opts.secret_access_key.clone().into(),
+    };
+    let backend = match opts.service_type.as_str() {
+        "" => return Ok(None),
+        "fs" => StorageBackend::Fs {
+            root: opts.s3_root.clone(),
+        },
+        "s3" => StorageBackend::S3(object_storage()),
+        "oss" => StorageBackend::Oss(object_storage()),
+        "obs" => StorageBackend::Obs(object_storage()),
+        "cos" => StorageBackend::Cos(object_storage()),
+        "azblob" => StorageBackend::Azblob(AzblobStorage {
+            endpoint: opts.s3_endpoint.clone(),
+            container: opts.s3_bucket.clone(),
+            root: opts.s3_root.clone(),
+            account_name: opts.access_key_id.clone().into(),
+            account_key: opts.secret_access_key.clone().into(),
+        }),
+        service_type => {
+            println!("unsupported service type: {service_type}");
+            return Err(Error::InvalidStorageConfig);
+        }
+    };
+    backend.check().map_err(|e| {
+        println!("invalid {} storage backend: {e}", backend.service_type());
+        Error::InvalidStorageConfig
+    })?;
+    Ok(Some(backend))
+}
+
 /// execute init node
 pub fn execute_init_node(opts: InitNodeOpts) -> Result<(), Error> {
+    let before_hash = chain_config_hash(&opts.config_dir, &opts.chain_name);
     let file_name = format!(
         "{}/{}/{}",
         &opts.config_dir, &opts.chain_name, CHAIN_CONFIG_FILE
@@ -123,6 +251,7 @@ pub fn execute_init_node(opts: InitNodeOpts) -> Result<(), Error> {
 
     if Path::new(&file_name).exists() {
         let chain_config = read_chain_config(&file_name).unwrap();
+        chain_config.check_genesis_hash()?;
         // gen node config after chain config stage is Finalize
         if chain_config.stage != ConfigStage::Finalize {
             return Err(Error::InvalidStage);
@@ -132,6 +261,9 @@ pub fn execute_init_node(opts: InitNodeOpts) -> Result<(), Error> {
     }
 
     let account = remove_0x(opts.account.as_str());
+    let service_log = parse_service_log(&opts)?;
+    let tracing = parse_tracing(&opts)?;
+    let storage_backend = parse_storage_backend(&opts)?;
 
     let grpc_ports = GrpcPortsBuilder::default()
         .network_port(opts.network_port)
@@ -147,15 +279,6 @@ pub fn execute_init_node(opts: InitNodeOpts) -> Result<(), Error> {
         .storage_metrics_port(opts.storage_metrics_port)
         .controller_metrics_port(opts.controller_metrics_port)
         .build();
-    let cloud_storage = CloudStorageBuilder::default()
-        .access_key_id(opts.access_key_id.clone())
-        .secret_access_key(opts.secret_access_key.clone())
-        .endpoint(opts.s3_endpoint.clone())
-        .bucket(opts.s3_bucket.clone())
-        .service_type(opts.service_type.clone())
-        .root(opts.s3_root.clone())
-        .region(opts.s3_region.clone())
-        .build();
     let exporter = ExportConfig {
         base_path: opts.exporter_path.clone(),
         chain_name: opts.chain_name.clone(),
@@ -166,13 +289,20 @@ pub fn execute_init_node(opts: InitNodeOpts) -> Result<(), Error> {
         .log_level(opts.log_level)
         .log_file_path(opts.log_file_path)
         .jaeger_agent_endpoint(opts.jaeger_agent_endpoint)
+        .tracing(tracing)
         .account(account.to_string())
         .enable_metrics(!opts.disable_metrics)
         .is_danger(opts.is_danger)
         .enable_tx_persistence(opts.enable_tx_persistence)
-        .cloud_storage(cloud_storage)
+        .storage_backend(storage_backend)
         .exporter(exporter)
+        .service_log(service_log)
+        .chaincode(ChaincodeTuning {
+            chaincode_port: opts.chaincode_port,
+            ..Default::default()
+        })
         .build();
+    node_config.check_log_config()?;
 
     let node_dir = format!("{}/{}-{}", &opts.config_dir, &opts.chain_name, &opts.domain);
     fs::create_dir_all(&node_dir).unwrap();
@@ -210,5 +340,12 @@ pub fn execute_init_node(opts: InitNodeOpts) -> Result<(), Error> {
     let file_name = format!("{}/{}", &node_dir, NODE_CONFIG_FILE);
     write_toml(node_config, file_name);
 
+    audit(
+        &opts.config_dir,
+        &opts.chain_name,
+        "init-node",
+        &before_hash,
+    );
+
     Ok(())
 }
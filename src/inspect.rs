@@ -0,0 +1,68 @@
+// Copyright Rivtower Technologies LLC.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::config::chain_config::ChainConfig;
+use crate::constant::CHAIN_CONFIG_FILE;
+use crate::error::Error;
+use crate::util::read_chain_config;
+use clap::Parser;
+
+/// A subcommand for run
+#[derive(Parser, Debug, Clone)]
+pub struct InspectOpts {
+    /// set chain name
+    #[clap(long = "chain-name", default_value = "test-chain")]
+    pub chain_name: String,
+    /// set config file directory, default means current directory
+    #[clap(long = "config-dir", default_value = ".")]
+    pub config_dir: String,
+}
+
+/// print summary of chain config
+pub fn execute_inspect(opts: InspectOpts) -> Result<ChainConfig, Error> {
+    // load chain_config
+    let file_name = format!(
+        "{}/{}/{}",
+        &opts.config_dir, &opts.chain_name, CHAIN_CONFIG_FILE
+    );
+    let chain_config = read_chain_config(file_name).unwrap();
+
+    println!("chain_name: {}", &opts.chain_name);
+    println!("stage: {:?}", &chain_config.stage);
+    println!("admin: {}", &chain_config.system_config.admin);
+    println!("validators:");
+    for validator in chain_config.current_validators() {
+        println!("    {validator}");
+    }
+    println!("nodes:");
+    for node in &chain_config.node_network_address_list {
+        let cluster = if node.cluster.is_empty() {
+            "-"
+        } else {
+            &node.cluster
+        };
+        println!(
+            "    {} role: {} address: {}:{} protocol: {} cluster: {} namespace: {}",
+            &node.domain,
+            node.role,
+            &node.host,
+            node.port,
+            node.protocol,
+            cluster,
+            &node.name_space
+        );
+    }
+
+    Ok(chain_config)
+}
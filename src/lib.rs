@@ -14,8 +14,12 @@
 
 pub mod append_node;
 pub mod append_validator;
+pub mod approve_config;
+pub mod audit;
 pub mod cmd;
 pub mod config;
+pub mod config_merge;
+pub mod confirm_validators;
 pub mod constant;
 pub mod create_ca;
 pub mod create_csr;
@@ -23,18 +27,33 @@ pub mod delete_chain;
 pub mod delete_node;
 pub mod delete_validator;
 pub mod error;
+pub mod export_join_request;
+pub mod genesis_hash;
+pub mod history;
 pub mod import_account;
 pub mod import_ca;
 pub mod import_cert;
 pub mod init_chain;
 pub mod init_chain_config;
 pub mod init_node;
+pub mod inspect;
+pub mod merge_join_requests;
+pub mod migrate;
+pub mod migration;
 pub mod new_account;
+pub mod overrides;
+pub mod port_allocator;
+pub mod propose_validators;
+pub mod registry;
 pub mod set_admin;
+pub mod set_image;
 pub mod set_nodelist;
+pub mod set_overlord;
 pub mod set_stage;
+pub mod set_topology;
 pub mod set_validators;
 pub mod sign_csr;
+pub mod topology;
 pub mod traits;
 pub mod update_node;
 pub mod update_yaml;
@@ -16,34 +16,51 @@ use clap::Parser;
 
 use crate::append_node::{execute_append_node, AppendNodeOpts};
 use crate::append_validator::{execute_append_validator, AppendValidatorOpts};
+use crate::approve_config::{execute_approve_config, ApproveConfigOpts};
 use crate::cmd::{
     execute_append, execute_create, execute_delete, AppendOpts, CreateOpts, DeleteOpts,
 };
+use crate::confirm_validators::{execute_confirm_validators, ConfirmValidatorsOpts};
 use crate::create_ca::{execute_create_ca, CreateCAOpts};
 use crate::create_csr::{execute_create_csr, CreateCSROpts};
 use crate::delete_chain::{execute_delete_chain, DeleteChainOpts};
 use crate::delete_node::{execute_delete_node, DeleteNodeOpts};
 use crate::delete_validator::{execute_delete_validator, DeleteValidatorOpts};
+use crate::export_join_request::{execute_export_join_request, ExportJoinRequestOpts};
+use crate::genesis_hash::{execute_genesis_hash, GenesisHashOpts};
+use crate::history::{execute_history, HistoryOpts};
 use crate::import_account::{execute_import_account, ImportAccountOpts};
 use crate::import_ca::{execute_import_ca, ImportCAOpts};
 use crate::import_cert::{execute_import_cert, ImportCertOpts};
 use crate::init_chain::{execute_init_chain, InitChainOpts};
 use crate::init_chain_config::{execute_init_chain_config, InitChainConfigOpts};
 use crate::init_node::{execute_init_node, InitNodeOpts};
+use crate::inspect::{execute_inspect, InspectOpts};
+use crate::merge_join_requests::{execute_merge_join_requests, MergeJoinRequestsOpts};
+use crate::migrate::{execute_migrate, MigrateOpts};
 use crate::new_account::{execute_new_account, NewAccountOpts};
+use crate::propose_validators::{execute_propose_validators, ProposeValidatorsOpts};
 use crate::set_admin::{execute_set_admin, SetAdminOpts};
+use crate::set_image::{execute_set_image, SetImageOpts};
 use crate::set_nodelist::{execute_set_nodelist, SetNodeListOpts};
+use crate::set_overlord::{execute_set_overlord, SetOverlordOpts};
 use crate::set_stage::{execute_set_stage, SetStageOpts};
+use crate::set_topology::{execute_set_topology, SetTopologyOpts};
 use crate::set_validators::{execute_set_validators, SetValidatorsOpts};
 use crate::sign_csr::{execute_sign_csr, SignCSROpts};
+use crate::topology::{execute_topology, TopologyOpts};
 use crate::update_node::{execute_update_node, UpdateNodeOpts};
 use crate::update_yaml::{execute_update_yaml, UpdateYamlOpts};
 use crate::util::clap_about;
 
 mod append_node;
 mod append_validator;
+mod approve_config;
+mod audit;
 mod cmd;
 mod config;
+mod config_merge;
+mod confirm_validators;
 mod constant;
 mod create_ca;
 mod create_csr;
@@ -51,18 +68,33 @@ mod delete_chain;
 mod delete_node;
 mod delete_validator;
 mod error;
+mod export_join_request;
+mod genesis_hash;
+mod history;
 mod import_account;
 mod import_ca;
 mod import_cert;
 mod init_chain;
 mod init_chain_config;
 mod init_node;
+mod inspect;
+mod merge_join_requests;
+mod migrate;
+mod migration;
 mod new_account;
+mod overrides;
+mod port_allocator;
+mod propose_validators;
+mod registry;
 mod set_admin;
+mod set_image;
 mod set_nodelist;
+mod set_overlord;
 mod set_stage;
+mod set_topology;
 mod set_validators;
 mod sign_csr;
+mod topology;
 mod traits;
 mod update_node;
 mod update_yaml;
@@ -150,6 +182,45 @@ enum SubCommand {
     /// delete a validator from chain
     #[clap(name = "delete-validator")]
     DeleteValidator(DeleteValidatorOpts),
+    /// propose validators change of a running chain
+    #[clap(name = "propose-validators")]
+    ProposeValidators(ProposeValidatorsOpts),
+    /// confirm proposed validators after the transaction committed
+    #[clap(name = "confirm-validators")]
+    ConfirmValidators(ConfirmValidatorsOpts),
+    /// print genesis hash of chain
+    #[clap(name = "genesis-hash")]
+    GenesisHash(GenesisHashOpts),
+    /// sign chain config with node account
+    #[clap(name = "approve-config")]
+    ApproveConfig(ApproveConfigOpts),
+    /// export join request of node
+    #[clap(name = "export-join-request")]
+    ExportJoinRequest(ExportJoinRequestOpts),
+    /// merge join requests into chain
+    #[clap(name = "merge-join-requests")]
+    MergeJoinRequests(MergeJoinRequestsOpts),
+    /// show audit log of chain
+    #[clap(name = "history")]
+    History(HistoryOpts),
+    /// set network topology of chain
+    #[clap(name = "set-topology")]
+    SetTopology(SetTopologyOpts),
+    /// print network topology of chain as DOT
+    #[clap(name = "topology")]
+    Topology(TopologyOpts),
+    /// show summary of chain config
+    #[clap(name = "inspect")]
+    Inspect(InspectOpts),
+    /// set image of micro service
+    #[clap(name = "set-image")]
+    SetImage(SetImageOpts),
+    /// migrate config of chain to current schema version
+    #[clap(name = "migrate")]
+    Migrate(MigrateOpts),
+    /// set params of consensus_overlord
+    #[clap(name = "set-overlord")]
+    SetOverlord(SetOverlordOpts),
 }
 
 fn main() {
@@ -182,5 +253,22 @@ fn main() {
         SubCommand::ImportCert(opts) => execute_import_cert(opts).map(|_| ()).unwrap(),
         SubCommand::UpdateYaml(opts) => execute_update_yaml(opts).map(|_| ()).unwrap(),
         SubCommand::DeleteValidator(opts) => execute_delete_validator(opts).unwrap(),
+        SubCommand::ProposeValidators(opts) => {
+            execute_propose_validators(opts).map(|_| ()).unwrap()
+        }
+        SubCommand::ConfirmValidators(opts) => execute_confirm_validators(opts).unwrap(),
+        SubCommand::GenesisHash(opts) => execute_genesis_hash(opts).map(|_| ()).unwrap(),
+        SubCommand::ApproveConfig(opts) => execute_approve_config(opts).map(|_| ()).unwrap(),
+        SubCommand::ExportJoinRequest(opts) => {
+            execute_export_join_request(opts).map(|_| ()).unwrap()
+        }
+        SubCommand::MergeJoinRequests(opts) => execute_merge_join_requests(opts).unwrap(),
+        SubCommand::History(opts) => execute_history(opts).map(|_| ()).unwrap(),
+        SubCommand::SetTopology(opts) => execute_set_topology(opts).unwrap(),
+        SubCommand::Topology(opts) => execute_topology(opts).map(|_| ()).unwrap(),
+        SubCommand::Inspect(opts) => execute_inspect(opts).map(|_| ()).unwrap(),
+        SubCommand::SetImage(opts) => execute_set_image(opts).unwrap(),
+        SubCommand::Migrate(opts) => execute_migrate(opts).unwrap(),
+        SubCommand::SetOverlord(opts) => execute_set_overlord(opts).unwrap(),
     }
 }
@@ -0,0 +1,273 @@
+// Copyright Rivtower Technologies LLC.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::audit::{audit, chain_config_hash};
+use crate::config::chain_config::{check_domain, ConfigStage, NodeRole};
+use crate::constant::{CERTS_DIR, CHAIN_CONFIG_FILE, CSR_PEM};
+use crate::error::Error;
+use crate::export_join_request::JoinRequest;
+use crate::util::{parse_address, read_chain_config, read_file, write_file, write_toml};
+use clap::Parser;
+use rcgen::CertificateSigningRequestParams;
+use std::fs;
+
+/// A subcommand for run
+#[derive(Parser, Debug, Clone)]
+pub struct MergeJoinRequestsOpts {
+    /// set chain name
+    #[clap(long = "chain-name", default_value = "test-chain")]
+    pub chain_name: String,
+    /// set config file directory, default means current directory
+    #[clap(long = "config-dir", default_value = ".")]
+    pub config_dir: String,
+    /// join request files splited by ','
+    #[clap(long = "requests")]
+    pub requests: String,
+}
+
+/// execute merge join requests
+/// all requests are validated before merge
+/// nothing changed if any conflict found
+pub fn execute_merge_join_requests(opts: MergeJoinRequestsOpts) -> Result<(), Error> {
+    let before_hash = chain_config_hash(&opts.config_dir, &opts.chain_name);
+    // load chain_config
+    let file_name = format!(
+        "{}/{}/{}",
+        &opts.config_dir, &opts.chain_name, CHAIN_CONFIG_FILE
+    );
+    let mut chain_config = read_chain_config(&file_name).unwrap();
+    chain_config.check_genesis_hash()?;
+
+    if chain_config.stage != ConfigStage::Public {
+        return Err(Error::InvalidStage);
+    }
+
+    let mut node_list = chain_config.node_network_address_list.clone();
+    let mut validators = chain_config.system_config.validators.clone();
+    let mut join_requests = Vec::new();
+    let mut conflicts = Vec::new();
+
+    for request_file in opts.requests.split(',') {
+        let join_request = match read_file(request_file)
+            .ok()
+            .and_then(|s| toml::from_str::<JoinRequest>(&s).ok())
+        {
+            Some(join_request) => join_request,
+            None => {
+                conflicts.push(format!("{request_file}: invalid join request file"));
+                continue;
+            }
+        };
+
+        let node = &join_request.node;
+        if join_request.signature.is_none() && node.role == NodeRole::Validator {
+            conflicts.push(format!(
+                "{request_file}: join request of validator must be signed"
+            ));
+        } else if !join_request.verify() {
+            conflicts.push(format!(
+                "{request_file}: signature not match node address {}",
+                &join_request.node_address
+            ));
+        }
+        if let Err(e) = check_domain(&node.domain) {
+            conflicts.push(format!("{request_file}: {e}"));
+        }
+        if let Some(dup) = node_list.iter().find(|n| n.domain == node.domain) {
+            conflicts.push(format!(
+                "{request_file}: duplicate domain {} with {}:{}",
+                &node.domain, &dup.host, dup.port
+            ));
+        }
+        if let Some(dup) = node_list
+            .iter()
+            .find(|n| n.host == node.host && n.port == node.port)
+        {
+            conflicts.push(format!(
+                "{request_file}: duplicate host and port {}:{} with node {}",
+                &node.host, node.port, &dup.domain
+            ));
+        }
+        let validator = match parse_address(&join_request.validator_address) {
+            Ok(validator) => validator.to_string(),
+            Err(e) => {
+                conflicts.push(format!("{request_file}: {e}"));
+                join_request.validator_address.clone()
+            }
+        };
+        if let Err(e) = parse_address(&join_request.node_address) {
+            conflicts.push(format!("{request_file}: {e}"));
+        }
+        let is_validator = node.role == NodeRole::Validator;
+        if is_validator && validators.contains(&validator) {
+            conflicts.push(format!("{request_file}: duplicate validator {validator}"));
+        }
+        if CertificateSigningRequestParams::from_pem(&join_request.csr).is_err() {
+            conflicts.push(format!("{request_file}: invalid csr"));
+        }
+
+        node_list.push(node.clone());
+        if is_validator {
+            validators.push(validator);
+        }
+        join_requests.push(join_request);
+    }
+
+    if !conflicts.is_empty() {
+        for conflict in &conflicts {
+            println!("{conflict}");
+        }
+        return Err(Error::JoinRequestConflict);
+    }
+
+    // store csr for sign-csr
+    for join_request in &join_requests {
+        let path = format!(
+            "{}/{}/{}/{}",
+            &opts.config_dir, &opts.chain_name, CERTS_DIR, &join_request.node.domain
+        );
+        fs::create_dir_all(&path).unwrap();
+        write_file(
+            join_request.csr.as_bytes(),
+            format!("{}/{}", &path, CSR_PEM),
+        );
+        println!(
+            "merged node: {} validator: {}",
+            &join_request.node.domain, &join_request.validator_address
+        );
+    }
+
+    chain_config.set_node_network_address_list(node_list);
+    chain_config.set_validators(validators);
+
+    // store chain_config
+    write_toml(&chain_config, file_name);
+
+    audit(
+        &opts.config_dir,
+        &opts.chain_name,
+        "merge-join-requests",
+        &before_hash,
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod merge_join_requests_test {
+    use super::*;
+    use crate::create_csr::{execute_create_csr, CreateCSROpts};
+    use crate::export_join_request::{execute_export_join_request, ExportJoinRequestOpts};
+    use crate::init_chain::{execute_init_chain, InitChainOpts};
+    use crate::init_chain_config::{execute_init_chain_config, InitChainConfigOpts};
+    use crate::new_account::{execute_new_account, NewAccountOpts};
+    use crate::set_admin::{execute_set_admin, SetAdminOpts};
+
+    #[test]
+    fn merge_test() {
+        let config_dir = "/tmp/test-merge-join-requests";
+        let chain_dir = format!("{config_dir}/test-chain");
+        let _ = fs::remove_dir_all(config_dir);
+        let args = ["--config-dir", config_dir];
+        execute_init_chain(InitChainOpts::parse_from(
+            ["init-chain"].iter().chain(&args),
+        ))
+        .unwrap();
+        execute_init_chain_config(InitChainConfigOpts::parse_from(
+            ["init-chain-config"].iter().chain(&args),
+        ))
+        .unwrap();
+        execute_set_admin(SetAdminOpts::parse_from(
+            [
+                "set-admin",
+                "--admin",
+                "a81a6d5ebf5bb612dd52b37f743d2eb7a90807f7",
+            ]
+            .iter()
+            .chain(&args),
+        ))
+        .unwrap();
+
+        let (account, validator) = execute_new_account(NewAccountOpts::parse_from(
+            ["new-account"].iter().chain(&args),
+        ))
+        .unwrap();
+        execute_create_csr(CreateCSROpts::parse_from(
+            ["create-csr", "--domain", "node0"].iter().chain(&args),
+        ))
+        .unwrap();
+        let join_request = execute_export_join_request(ExportJoinRequestOpts::parse_from(
+            [
+                "export-join-request",
+                "--node",
+                "localhost:40000:node0",
+                "--account",
+                &account,
+                "--sign",
+            ]
+            .iter()
+            .chain(&args),
+        ))
+        .unwrap();
+        let request_file = format!("{chain_dir}/node0_join_request.toml");
+
+        // malformed address is a conflict, nothing merged
+        let mut bad_request = join_request.clone();
+        bad_request.node = "localhost:40001:node1".parse().unwrap();
+        bad_request.validator_address = "0xnot-an-address".to_string();
+        bad_request.signature = None;
+        let bad_request_file = format!("{chain_dir}/bad_join_request.toml");
+        write_toml(&bad_request, &bad_request_file);
+        let merge = |requests: String| {
+            execute_merge_join_requests(MergeJoinRequestsOpts {
+                chain_name: "test-chain".to_string(),
+                config_dir: config_dir.to_string(),
+                requests,
+            })
+        };
+        assert!(matches!(
+            merge(format!("{request_file},{bad_request_file}")),
+            Err(Error::JoinRequestConflict)
+        ));
+        let chain_config = read_chain_config(format!("{chain_dir}/{CHAIN_CONFIG_FILE}")).unwrap();
+        assert!(chain_config.node_network_address_list.is_empty());
+
+        // join request of validator must be signed
+        let mut unsigned_request = join_request.clone();
+        unsigned_request.signature = None;
+        let unsigned_request_file = format!("{chain_dir}/unsigned_join_request.toml");
+        write_toml(&unsigned_request, &unsigned_request_file);
+        assert!(matches!(
+            merge(unsigned_request_file),
+            Err(Error::JoinRequestConflict)
+        ));
+
+        merge(request_file.clone()).unwrap();
+        let chain_config = read_chain_config(format!("{chain_dir}/{CHAIN_CONFIG_FILE}")).unwrap();
+        assert_eq!(chain_config.node_network_address_list.len(), 1);
+        assert_eq!(
+            chain_config.system_config.validators,
+            vec![validator.clone()]
+        );
+        assert!(fs::metadata(format!("{chain_dir}/{CERTS_DIR}/node0/{CSR_PEM}")).is_ok());
+
+        // merged again is duplicate
+        assert!(matches!(
+            merge(request_file),
+            Err(Error::JoinRequestConflict)
+        ));
+
+        fs::remove_dir_all(config_dir).unwrap();
+    }
+}
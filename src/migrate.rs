@@ -0,0 +1,143 @@
+// Copyright Rivtower Technologies LLC.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::audit::{audit, chain_config_hash};
+use crate::config::chain_config::ConfigStage;
+use crate::constant::{CHAIN_CONFIG_FILE, NODE_CONFIG_FILE, SCHEMA_VERSION};
+use crate::error::Error;
+use crate::migration::schema_version;
+use crate::util::{load_chain_config, load_node_config, read_chain_config, read_file, write_toml};
+use clap::Parser;
+use serde::Serialize;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use toml::Table;
+
+/// A subcommand for run
+#[derive(Parser, Debug, Clone)]
+pub struct MigrateOpts {
+    /// set chain name
+    #[clap(long = "chain-name", default_value = "test-chain")]
+    pub chain_name: String,
+    /// set config file directory, default means current directory
+    #[clap(long = "config-dir", default_value = ".")]
+    pub config_dir: String,
+    /// backfill genesis_hash of finalized chain without confirmation
+    #[clap(long = "yes")]
+    pub yes: bool,
+}
+
+/// upgrade config file to current schema in place
+/// original file is kept in {file_name}.v{version}.bak
+fn migrate_file<T: Serialize>(
+    file_name: &str,
+    read: fn(&str) -> Result<T, toml::de::Error>,
+) -> Result<(), Error> {
+    let table = read_file(file_name)
+        .unwrap()
+        .parse::<Table>()
+        .map_err(|e| {
+            println!("parse {file_name} failed: {e}");
+            Error::UnsupportedSchemaVersion
+        })?;
+    let version = schema_version(&table);
+    if version == SCHEMA_VERSION {
+        println!("{file_name} is up to date");
+        return Ok(());
+    }
+
+    let config = read(file_name).map_err(|e| {
+        println!("migrate {file_name} failed: {e}");
+        Error::UnsupportedSchemaVersion
+    })?;
+    fs::copy(file_name, format!("{file_name}.v{version}.bak")).unwrap();
+    write_toml(config, file_name);
+    println!("migrate {file_name} from v{version} to v{SCHEMA_VERSION}");
+    Ok(())
+}
+
+fn confirm(prompt: &str) -> bool {
+    print!("{prompt} [y/N] ");
+    io::stdout().flush().unwrap();
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer).is_ok() && answer.trim().eq_ignore_ascii_case("y")
+}
+
+/// chain finalized by old version has no genesis_hash
+/// it can only be computed over current genesis, which may have been edited, so user must confirm
+fn backfill_genesis_hash(files: &[String], yes: bool) -> Result<(), Error> {
+    let mut missing = Vec::new();
+    for file_name in files {
+        let chain_config = read_chain_config(file_name).unwrap();
+        if chain_config.stage == ConfigStage::Finalize && chain_config.genesis_hash.is_none() {
+            missing.push((file_name, chain_config));
+        }
+    }
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    println!("genesis_hash is missing in finalized chain_config, it will be computed over current genesis_block and system_config");
+    println!(
+        "make sure they are same as genesis of the running chain, otherwise the node will fork"
+    );
+    if !yes && !confirm("backfill genesis_hash?") {
+        println!("genesis_hash not backfilled");
+        return Err(Error::GenesisHashMissing);
+    }
+    for (file_name, mut chain_config) in missing {
+        let genesis_hash = chain_config.compute_genesis_hash();
+        println!("backfill genesis_hash of {file_name}: {genesis_hash}");
+        chain_config.genesis_hash = Some(genesis_hash);
+        write_toml(chain_config, file_name);
+    }
+    Ok(())
+}
+
+/// execute migrate
+pub fn execute_migrate(opts: MigrateOpts) -> Result<(), Error> {
+    let before_hash = chain_config_hash(&opts.config_dir, &opts.chain_name);
+    let file_name = format!(
+        "{}/{}/{}",
+        &opts.config_dir, &opts.chain_name, CHAIN_CONFIG_FILE
+    );
+    if !Path::new(&file_name).exists() {
+        println!("{file_name} not found");
+        return Err(Error::FileNoFound);
+    }
+    migrate_file(&file_name, |f| load_chain_config(f))?;
+    let mut chain_config_files = vec![file_name.clone()];
+
+    // nodes of this chain in config dir
+    let chain_config = read_chain_config(&file_name).unwrap();
+    for node in &chain_config.node_network_address_list {
+        let node_dir = format!("{}/{}-{}", &opts.config_dir, &opts.chain_name, &node.domain);
+        let file_name = format!("{}/{}", &node_dir, CHAIN_CONFIG_FILE);
+        if Path::new(&file_name).exists() {
+            migrate_file(&file_name, |f| load_chain_config(f))?;
+            chain_config_files.push(file_name);
+        }
+        let file_name = format!("{}/{}", &node_dir, NODE_CONFIG_FILE);
+        if Path::new(&file_name).exists() {
+            migrate_file(&file_name, |f| load_node_config(f))?;
+        }
+    }
+
+    backfill_genesis_hash(&chain_config_files, opts.yes)?;
+
+    audit(&opts.config_dir, &opts.chain_name, "migrate", &before_hash);
+
+    Ok(())
+}
@@ -0,0 +1,205 @@
+// Copyright Rivtower Technologies LLC.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::constant::SCHEMA_VERSION;
+use toml::{Table, Value};
+
+type Migration = fn(&mut Table);
+
+// the i-th migration upgrades schema from version i to i + 1
+const CHAIN_CONFIG_MIGRATIONS: [Migration; SCHEMA_VERSION as usize] =
+    [chain_config_v1, chain_config_v2];
+const NODE_CONFIG_MIGRATIONS: [Migration; SCHEMA_VERSION as usize] =
+    [node_config_v1, node_config_v2];
+
+// stage is missing in chain_config of old version, chains of which are already running
+fn chain_config_v1(config: &mut Table) {
+    config
+        .entry("stage")
+        .or_insert(Value::String("Finalize".to_string()));
+}
+
+// nothing changed in chain_config
+fn chain_config_v2(_config: &mut Table) {}
+
+// fields missing in node_config of old version
+fn node_config_v1(config: &mut Table) {
+    config
+        .entry("log_level")
+        .or_insert(Value::String("info".to_string()));
+    config
+        .entry("enable_metrics")
+        .or_insert(Value::Boolean(true));
+    config.entry("is_danger").or_insert(Value::Boolean(false));
+    config
+        .entry("enable_tx_persistence")
+        .or_insert(Value::Boolean(false));
+    config
+        .entry("exporter")
+        .or_insert(Value::Table(Table::new()));
+    if let Some(cloud_storage) = config
+        .entry("cloud_storage")
+        .or_insert(Value::Table(Table::new()))
+        .as_table_mut()
+    {
+        for key in [
+            "access_key_id",
+            "secret_access_key",
+            "endpoint",
+            "bucket",
+            "service_type",
+            "root",
+            "region",
+        ] {
+            cloud_storage
+                .entry(key)
+                .or_insert(Value::String(String::new()));
+        }
+    }
+}
+
+// flat cloud_storage is replaced by storage_backend tagged by type
+// empty service_type means no cloud storage
+fn node_config_v2(config: &mut Table) {
+    let Some(Value::Table(mut cloud_storage)) = config.remove("cloud_storage") else {
+        return;
+    };
+    let service_type = match cloud_storage.remove("service_type") {
+        Some(Value::String(service_type)) if !service_type.is_empty() => service_type,
+        _ => return,
+    };
+    if service_type == "azblob" {
+        for (from, to) in [
+            ("bucket", "container"),
+            ("access_key_id", "account_name"),
+            ("secret_access_key", "account_key"),
+        ] {
+            if let Some(value) = cloud_storage.remove(from) {
+                cloud_storage.insert(to.to_string(), value);
+            }
+        }
+    }
+    cloud_storage.insert("type".to_string(), Value::String(service_type));
+    config.insert("storage_backend".to_string(), Value::Table(cloud_storage));
+}
+
+/// schema version of config, missing means 0
+pub fn schema_version(config: &Table) -> u64 {
+    config
+        .get("schema_version")
+        .and_then(Value::as_integer)
+        .map_or(0, |version| version as u64)
+}
+
+fn migrate(config: &mut Table, migrations: &[Migration]) -> Result<u64, String> {
+    let version = schema_version(config);
+    if version > SCHEMA_VERSION {
+        return Err(format!(
+            "schema version {version} is newer than {SCHEMA_VERSION}, please upgrade cloud-config"
+        ));
+    }
+    for migration in &migrations[version as usize..] {
+        migration(config);
+    }
+    config.insert(
+        "schema_version".to_string(),
+        Value::Integer(SCHEMA_VERSION as i64),
+    );
+    Ok(version)
+}
+
+/// upgrade chain_config to current schema, returns original version
+pub fn migrate_chain_config(config: &mut Table) -> Result<u64, String> {
+    migrate(config, &CHAIN_CONFIG_MIGRATIONS)
+}
+
+/// upgrade node_config to current schema, returns original version
+pub fn migrate_node_config(config: &mut Table) -> Result<u64, String> {
+    migrate(config, &NODE_CONFIG_MIGRATIONS)
+}
+
+#[cfg(test)]
+mod migration_test {
+    use super::*;
+    use crate::config::chain_config::{ChainConfig, ChainConfigBuilder, ConfigStage};
+    use crate::config::node_config::{NodeConfig, NodeConfigBuilder};
+    use crate::config::storage_opendal::StorageBackend;
+    use crate::util::{load_chain_config, read_chain_config, write_toml};
+    use std::fs;
+
+    #[test]
+    fn migrate_test() {
+        let mut config = Table::try_from(ChainConfigBuilder::default().build()).unwrap();
+        config.remove("schema_version");
+        config.remove("stage");
+        assert_eq!(migrate_chain_config(&mut config), Ok(0));
+        let chain_config: ChainConfig = config.try_into().unwrap();
+        assert_eq!(chain_config.schema_version, SCHEMA_VERSION);
+        assert_eq!(chain_config.stage, ConfigStage::Finalize);
+        // genesis_hash is never computed on read
+        assert_eq!(chain_config.genesis_hash, None);
+        assert!(chain_config.check_genesis_hash().is_err());
+
+        let mut config = Table::try_from(NodeConfigBuilder::default().build()).unwrap();
+        assert_eq!(migrate_node_config(&mut config), Ok(SCHEMA_VERSION));
+        config.remove("schema_version");
+        config.remove("cloud_storage");
+        config.remove("exporter");
+        config.remove("enable_tx_persistence");
+        assert_eq!(migrate_node_config(&mut config), Ok(0));
+        let node_config: NodeConfig = config.try_into().unwrap();
+        assert_eq!(node_config.schema_version, SCHEMA_VERSION);
+        assert!(!node_config.enable_tx_persistence);
+        assert_eq!(node_config.storage_backend, None);
+
+        config = Table::try_from(node_config).unwrap();
+        config.insert("schema_version".to_string(), Value::Integer(1));
+        config.insert(
+            "cloud_storage".to_string(),
+            Value::Table(
+                r#"
+                access_key_id = "account"
+                secret_access_key = "key"
+                endpoint = "https://account.blob.core.windows.net"
+                bucket = "chain"
+                service_type = "azblob"
+                root = ""
+                region = ""
+                "#
+                .parse()
+                .unwrap(),
+            ),
+        );
+        assert_eq!(migrate_node_config(&mut config), Ok(1));
+        let node_config: NodeConfig = config.try_into().unwrap();
+        let Some(StorageBackend::Azblob(storage)) = node_config.storage_backend else {
+            panic!("storage_backend should be azblob");
+        };
+        assert_eq!(storage.container, "chain");
+        assert_eq!(String::from(storage.account_name), "account");
+
+        let mut config: Table = "schema_version = 10000".parse().unwrap();
+        assert!(migrate_node_config(&mut config).is_err());
+
+        // old schema version is refused outside migrate
+        let file_name = "/tmp/test-migration-chain_config.toml";
+        let mut config = Table::try_from(ChainConfigBuilder::default().build()).unwrap();
+        config.remove("schema_version");
+        write_toml(config, file_name);
+        assert!(read_chain_config(file_name).is_err());
+        let chain_config = load_chain_config(file_name).unwrap();
+        assert_eq!(chain_config.schema_version, SCHEMA_VERSION);
+        fs::remove_file(file_name).unwrap();
+    }
+}
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::audit::{audit, chain_config_hash};
 use crate::constant::{
     ACCOUNT_DIR, CHAIN_CONFIG_FILE, CONSENSUS_OVERLORD, NODE_ADDRESS, PRIVATE_KEY,
     VALIDATOR_ADDRESS,
@@ -37,12 +38,14 @@ pub struct NewAccountOpts {
 
 /// execute new account
 pub fn execute_new_account(opts: NewAccountOpts) -> Result<(String, String), Error> {
+    let before_hash = chain_config_hash(&opts.config_dir, &opts.chain_name);
     // load chain_config
     let file_name = format!(
         "{}/{}/{}",
         &opts.config_dir, &opts.chain_name, CHAIN_CONFIG_FILE
     );
     let chain_config = read_chain_config(file_name).unwrap();
+    chain_config.check_genesis_hash()?;
 
     // create new account
     // generate private key
@@ -89,5 +92,12 @@ pub fn execute_new_account(opts: NewAccountOpts) -> Result<(String, String), Err
     // output node_address and validator_address
     println!("node_address: {address} validator_address: {validator_address}");
 
+    audit(
+        &opts.config_dir,
+        &opts.chain_name,
+        "new-account",
+        &before_hash,
+    );
+
     Ok((address, validator_address))
 }
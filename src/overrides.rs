@@ -0,0 +1,144 @@
+// Copyright Rivtower Technologies LLC.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::constant::{GENESIS_BLOCK, OVERRIDES_FILE, SYSTEM_CONFIG};
+use crate::error::Error;
+use crate::registry::MicroServiceRegistry;
+use std::fs;
+use std::io::Write;
+use toml::{Table, Value};
+
+fn read_overrides(dir: &str) -> Result<Option<Table>, Error> {
+    let file_name = format!("{dir}/{OVERRIDES_FILE}");
+    match fs::read_to_string(&file_name) {
+        Ok(content) => content.parse::<Table>().map(Some).map_err(|e| {
+            println!("parse {file_name} failed: {e}");
+            Error::InvalidOverrides
+        }),
+        Err(_) => Ok(None),
+    }
+}
+
+/// merge tables in overrides into base recursively, other values are replaced
+pub fn deep_merge(base: &mut Table, overrides: Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key).and_then(Value::as_table_mut), value) {
+            (Some(base_table), Value::Table(table)) => deep_merge(base_table, table),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// check each section of overrides is a micro service used in config
+fn check_overrides(config: &Table, overrides: &Table) -> Result<(), Error> {
+    // sections of micro services which can be overridden
+    let registry = MicroServiceRegistry::default();
+    let service_sections = registry.sections();
+    for (section, value) in overrides {
+        if section == GENESIS_BLOCK || section == SYSTEM_CONFIG {
+            println!("section {section} is decided by chain_config and can't be overridden");
+            return Err(Error::InvalidOverrides);
+        }
+        if !service_sections.contains(&section.as_str()) {
+            println!("section {section} is not a known micro service");
+            return Err(Error::InvalidOverrides);
+        }
+        if !config.contains_key(section) {
+            println!("micro service {section} is not used by chain");
+            return Err(Error::InvalidOverrides);
+        }
+        if !value.is_table() {
+            println!("section {section} must be a table");
+            return Err(Error::InvalidOverrides);
+        }
+    }
+    Ok(())
+}
+
+/// apply overrides.toml of chain and then of node to generated config file
+/// config file is rewritten only when there are overrides
+pub fn apply_overrides(chain_dir: &str, node_dir: &str, config_file: &str) -> Result<(), Error> {
+    let chain_overrides = read_overrides(chain_dir)?;
+    let node_overrides = read_overrides(node_dir)?;
+    if chain_overrides.is_none() && node_overrides.is_none() {
+        return Ok(());
+    }
+
+    let mut config = fs::read_to_string(config_file)
+        .unwrap()
+        .parse::<Table>()
+        .unwrap();
+    for overrides in [chain_overrides, node_overrides].into_iter().flatten() {
+        check_overrides(&config, &overrides)?;
+        deep_merge(&mut config, overrides);
+    }
+
+    let mut file = fs::File::create(config_file).unwrap();
+    file.write_all(toml::to_string_pretty(&config).unwrap().as_bytes())
+        .unwrap();
+    Ok(())
+}
+
+#[cfg(test)]
+mod overrides_test {
+    use super::*;
+
+    #[test]
+    fn merge_test() {
+        let mut config: Table = r#"
+            [controller]
+            domain = "test-chain-node0"
+            is_danger = false
+
+            [controller.log_config]
+            max_level = "info"
+            filter = "info"
+
+            [genesis_block]
+            timestamp = 0
+            "#
+        .parse()
+        .unwrap();
+
+        let overrides: Table = r#"
+            [controller]
+            is_danger = true
+            sync_block_num = 200
+
+            [controller.log_config]
+            filter = "debug"
+            "#
+        .parse()
+        .unwrap();
+        check_overrides(&config, &overrides).unwrap();
+        deep_merge(&mut config, overrides);
+
+        let controller = config["controller"].as_table().unwrap();
+        assert_eq!(controller["domain"].as_str(), Some("test-chain-node0"));
+        assert_eq!(controller["is_danger"].as_bool(), Some(true));
+        assert_eq!(controller["sync_block_num"].as_integer(), Some(200));
+        let log_config = controller["log_config"].as_table().unwrap();
+        assert_eq!(log_config["max_level"].as_str(), Some("info"));
+        assert_eq!(log_config["filter"].as_str(), Some("debug"));
+
+        let overrides: Table = "[genesis_block]\ntimestamp = 1".parse().unwrap();
+        assert!(check_overrides(&config, &overrides).is_err());
+        let overrides: Table = "[unknown]\nfoo = 1".parse().unwrap();
+        assert!(check_overrides(&config, &overrides).is_err());
+        let overrides: Table = "[executor_evm]\nfoo = 1".parse().unwrap();
+        assert!(check_overrides(&config, &overrides).is_err());
+    }
+}
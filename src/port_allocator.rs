@@ -0,0 +1,237 @@
+// Copyright Rivtower Technologies LLC.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::constant::{
+    CHAIN_CONFIG_FILE, EXECUTOR_CHAINCODE, NODE_CONFIG_FILE, PORT_ALLOCATIONS_FILE,
+};
+use crate::traits::PortAllocator;
+use crate::util::{find_micro_service, load_chain_config, load_node_config, read_file, write_toml};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::net::TcpListener;
+
+// network, consensus, executor, storage, controller
+const SERVICE_COUNT: u16 = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortAllocation {
+    pub domain: String,
+    pub grpc_port: u16,
+    pub metrics_port: u16,
+    // single ports allocated besides blocks, like chaincode port
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extra_ports: Vec<u16>,
+}
+
+/// allocations are never removed, so ports of deleted nodes are not reused
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct PortAllocations {
+    #[serde(default)]
+    pub allocation: Vec<PortAllocation>,
+}
+
+/// allocate ports in blocks of SERVICE_COUNT from base ports
+/// skip ports used by node configs in config dir, recorded allocations
+/// and ports bound on this machine if check_bind is true
+pub struct LocalPortAllocator {
+    grpc_port_base: u16,
+    metrics_port_base: u16,
+    check_bind: bool,
+    used_ports: HashSet<u16>,
+    allocations: PortAllocations,
+    allocations_file: String,
+}
+
+impl LocalPortAllocator {
+    pub fn new(
+        config_dir: &str,
+        chain_name: &str,
+        grpc_port_base: u16,
+        metrics_port_base: u16,
+        check_bind: bool,
+    ) -> Self {
+        let allocations_file = format!("{config_dir}/{chain_name}/{PORT_ALLOCATIONS_FILE}");
+        let allocations = read_file(&allocations_file)
+            .ok()
+            .and_then(|s| toml::from_str::<PortAllocations>(&s).ok())
+            .unwrap_or_default();
+
+        let mut used_ports = HashSet::new();
+        for allocation in &allocations.allocation {
+            for i in 0..SERVICE_COUNT {
+                used_ports.insert(allocation.grpc_port + i);
+                used_ports.insert(allocation.metrics_port + i);
+            }
+            used_ports.extend(&allocation.extra_ports);
+        }
+
+        // ports of all nodes in config dir, maybe belong to other chains
+        if let Ok(entries) = fs::read_dir(config_dir) {
+            for entry in entries.flatten() {
+                let file_name = entry.path().join(NODE_CONFIG_FILE);
+                if !file_name.exists() {
+                    continue;
+                }
+                if let Ok(node_config) = load_node_config(file_name) {
+                    let grpc_ports = &node_config.grpc_ports;
+                    let metrics_ports = &node_config.metrics_ports;
+                    used_ports.extend([
+                        grpc_ports.network_port,
+                        grpc_ports.consensus_port,
+                        grpc_ports.executor_port,
+                        grpc_ports.storage_port,
+                        grpc_ports.controller_port,
+                        metrics_ports.network_metrics_port,
+                        metrics_ports.consensus_metrics_port,
+                        metrics_ports.executor_metrics_port,
+                        metrics_ports.storage_metrics_port,
+                        metrics_ports.controller_metrics_port,
+                    ]);
+                    // chaincode port is listened only if executor of the chain is chaincode
+                    let is_chaincode = load_chain_config(entry.path().join(CHAIN_CONFIG_FILE))
+                        .map(|chain_config| find_micro_service(&chain_config, EXECUTOR_CHAINCODE))
+                        .unwrap_or(false);
+                    if is_chaincode {
+                        used_ports.insert(node_config.chaincode.chaincode_port());
+                    }
+                }
+            }
+        }
+
+        Self {
+            grpc_port_base,
+            metrics_port_base,
+            check_bind,
+            used_ports,
+            allocations,
+            allocations_file,
+        }
+    }
+
+    fn is_free(&self, port: u16) -> bool {
+        if self.used_ports.contains(&port) {
+            return false;
+        }
+        !self.check_bind || TcpListener::bind(("127.0.0.1", port)).is_ok()
+    }
+
+    fn find_block(&self, base: u16) -> u16 {
+        let mut start = base as u32;
+        while start + SERVICE_COUNT as u32 <= u16::MAX as u32 + 1 {
+            let port = start as u16;
+            if (0..SERVICE_COUNT).all(|i| self.is_free(port + i)) {
+                return port;
+            }
+            start += SERVICE_COUNT as u32;
+        }
+        panic!("no free ports from {base}");
+    }
+}
+
+impl PortAllocator for LocalPortAllocator {
+    fn allocate(&mut self, domain: &str) -> (u16, u16) {
+        let grpc_port = self.find_block(self.grpc_port_base);
+        self.used_ports
+            .extend((0..SERVICE_COUNT).map(|i| grpc_port + i));
+        let metrics_port = self.find_block(self.metrics_port_base);
+        self.used_ports
+            .extend((0..SERVICE_COUNT).map(|i| metrics_port + i));
+
+        self.allocations.allocation.push(PortAllocation {
+            domain: domain.to_string(),
+            grpc_port,
+            metrics_port,
+            extra_ports: Vec::new(),
+        });
+        write_toml(&self.allocations, &self.allocations_file);
+
+        (grpc_port, metrics_port)
+    }
+
+    fn allocate_port(&mut self, domain: &str, base: u16) -> u16 {
+        let port = (base..=u16::MAX)
+            .find(|port| self.is_free(*port))
+            .unwrap_or_else(|| panic!("no free ports from {base}"));
+        self.used_ports.insert(port);
+
+        match self
+            .allocations
+            .allocation
+            .iter_mut()
+            .rev()
+            .find(|allocation| allocation.domain == domain)
+        {
+            Some(allocation) => allocation.extra_ports.push(port),
+            None => panic!("ports of {domain} are not allocated"),
+        }
+        write_toml(&self.allocations, &self.allocations_file);
+
+        port
+    }
+}
+
+#[cfg(test)]
+mod port_allocator_test {
+    use super::*;
+    use crate::config::chain_config::{ChainConfigBuilder, MicroServiceBuilder};
+    use crate::config::executor_chaincode::ChaincodeTuning;
+    use crate::config::node_config::NodeConfigBuilder;
+
+    #[test]
+    fn allocate_test() {
+        let config_dir = "/tmp/test-port-allocator";
+        let chain_name = "test-chain";
+        let _ = fs::remove_dir_all(config_dir);
+        fs::create_dir_all(format!("{config_dir}/{chain_name}")).unwrap();
+
+        let mut allocator = LocalPortAllocator::new(config_dir, chain_name, 50000, 60000, false);
+        assert_eq!(allocator.allocate("node0"), (50000, 60000));
+        assert_eq!(allocator.allocate("node1"), (50005, 60005));
+
+        // chaincode port of local node is reserved only if executor is chaincode
+        let node_dir = format!("{config_dir}/{chain_name}-node9");
+        fs::create_dir_all(&node_dir).unwrap();
+        let write_node = |executor: &str, chaincode_port: u16| {
+            let chain_config = ChainConfigBuilder::default()
+                .micro_service_list(vec![MicroServiceBuilder::default()
+                    .image(executor.to_string())
+                    .build()])
+                .build();
+            write_toml(&chain_config, format!("{node_dir}/{CHAIN_CONFIG_FILE}"));
+            let node_config = NodeConfigBuilder::default()
+                .chaincode(ChaincodeTuning {
+                    chaincode_port: Some(chaincode_port),
+                    ..Default::default()
+                })
+                .build();
+            write_toml(&node_config, format!("{node_dir}/{NODE_CONFIG_FILE}"));
+        };
+        write_node("executor_evm", 7052);
+
+        // recorded allocations are not reused
+        let mut allocator = LocalPortAllocator::new(config_dir, chain_name, 50000, 60000, false);
+        assert_eq!(allocator.allocate("node2"), (50010, 60010));
+        assert_eq!(allocator.allocate_port("node2", 7052), 7052);
+
+        // extra ports are not reused either
+        write_node(EXECUTOR_CHAINCODE, 7053);
+        let mut allocator = LocalPortAllocator::new(config_dir, chain_name, 50000, 60000, false);
+        assert_eq!(allocator.allocate("node3"), (50015, 60015));
+        assert_eq!(allocator.allocate_port("node3", 7052), 7054);
+
+        fs::remove_dir_all(config_dir).unwrap();
+    }
+}
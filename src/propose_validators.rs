@@ -0,0 +1,252 @@
+// Copyright Rivtower Technologies LLC.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::audit::{audit, chain_config_hash};
+use crate::config::chain_config::ConfigStage;
+use crate::constant::{
+    ACCOUNT_DIR, CHAIN_CONFIG_FILE, LOCK_ID_VALIDATORS, PRE_HASH, PRIVATE_KEY,
+    VALIDATORS_PROPOSAL_FILE,
+};
+use crate::error::Error;
+use crate::util::{
+    crypto_hash, parse_address, read_chain_config, read_file, remove_0x, sign_message, write_toml,
+};
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+
+/// A subcommand for run
+#[derive(Parser, Debug, Clone)]
+pub struct ProposeValidatorsOpts {
+    /// set chain name
+    #[clap(long = "chain-name", default_value = "test-chain")]
+    pub chain_name: String,
+    /// set config file directory, default means current directory
+    #[clap(long = "config-dir", default_value = ".")]
+    pub config_dir: String,
+    /// desired validators account splited by ','
+    #[clap(long = "validators")]
+    pub validators: String,
+    /// admin account which private key is in accounts folder, default is admin of chain
+    #[clap(long = "admin")]
+    pub admin: Option<String>,
+    /// hash of last utxo transaction which changed validators, default is the last confirmed one
+    #[clap(long = "pre-tx-hash")]
+    pub pre_tx_hash: Option<String>,
+    /// set proposal file name
+    #[clap(long = "output", default_value = "validators_proposal.toml")]
+    pub output: String,
+}
+
+/// admin transaction which update validators of a running chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorsProposal {
+    pub validators: Vec<String>,
+    pub version: u32,
+    pub lock_id: u64,
+    pub pre_tx_hash: String,
+    // system contract call data, validators concatenated
+    pub output: String,
+    pub tx_hash: String,
+    pub sender: String,
+    pub signature: String,
+    // hex of encoded RawTransaction, can be sent to controller directly
+    pub raw_tx: String,
+}
+
+fn encode_varint(mut value: u64, buf: &mut Vec<u8>) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+// proto3 omits fields with default value
+fn encode_varint_field(tag: u32, value: u64, buf: &mut Vec<u8>) {
+    if value != 0 {
+        encode_varint((tag << 3) as u64, buf);
+        encode_varint(value, buf);
+    }
+}
+
+fn encode_bytes_field(tag: u32, bytes: &[u8], buf: &mut Vec<u8>) {
+    if !bytes.is_empty() {
+        encode_varint(((tag << 3) | 2) as u64, buf);
+        encode_varint(bytes.len() as u64, buf);
+        buf.extend_from_slice(bytes);
+    }
+}
+
+/// protobuf encoding of UtxoTransaction
+fn encode_utxo_transaction(
+    version: u32,
+    pre_tx_hash: &[u8],
+    output: &[u8],
+    lock_id: u64,
+) -> Vec<u8> {
+    let mut buf = Vec::new();
+    encode_varint_field(1, version as u64, &mut buf);
+    encode_bytes_field(2, pre_tx_hash, &mut buf);
+    encode_bytes_field(3, output, &mut buf);
+    encode_varint_field(4, lock_id, &mut buf);
+    buf
+}
+
+/// protobuf encoding of RawTransaction which wrap an UnverifiedUtxoTransaction
+fn encode_raw_utxo_transaction(
+    transaction: &[u8],
+    transaction_hash: &[u8],
+    signature: &[u8],
+    sender: &[u8],
+) -> Vec<u8> {
+    let mut witness = Vec::new();
+    encode_bytes_field(1, signature, &mut witness);
+    encode_bytes_field(2, sender, &mut witness);
+
+    let mut utxo_tx = Vec::new();
+    encode_bytes_field(1, transaction, &mut utxo_tx);
+    encode_bytes_field(2, transaction_hash, &mut utxo_tx);
+    encode_bytes_field(3, &witness, &mut utxo_tx);
+
+    let mut raw_tx = Vec::new();
+    encode_bytes_field(2, &utxo_tx, &mut raw_tx);
+    raw_tx
+}
+
+/// execute propose validators
+/// chain is running, so genesis validators in system_config keep unchanged
+/// new validators recorded as pending until confirm-validators
+pub fn execute_propose_validators(
+    opts: ProposeValidatorsOpts,
+) -> Result<ValidatorsProposal, Error> {
+    let before_hash = chain_config_hash(&opts.config_dir, &opts.chain_name);
+    // load chain_config
+    let file_name = format!(
+        "{}/{}/{}",
+        &opts.config_dir, &opts.chain_name, CHAIN_CONFIG_FILE
+    );
+    let mut chain_config = read_chain_config(&file_name).unwrap();
+    chain_config.check_genesis_hash()?;
+
+    if chain_config.stage != ConfigStage::Finalize {
+        return Err(Error::InvalidStage);
+    }
+
+    let mut validators: Vec<String> = Vec::new();
+    for validator in opts.validators.split(',') {
+        let validator = parse_address(validator).map_err(|e| {
+            println!("{e}");
+            Error::InvalidValidators
+        })?;
+        if validators.iter().any(|v| v == validator) {
+            println!("duplicate validator {validator}");
+            return Err(Error::InvalidValidators);
+        }
+        validators.push(validator.to_string());
+    }
+    if &validators == chain_config.current_validators() {
+        println!("validators not changed");
+        return Err(Error::InvalidValidators);
+    }
+
+    // load admin private key
+    let admin = match &opts.admin {
+        Some(admin) => remove_0x(admin).to_string(),
+        None => chain_config.system_config.admin.clone(),
+    };
+    let private_key_path = format!(
+        "{}/{}/{}/{}/{}",
+        &opts.config_dir, &opts.chain_name, ACCOUNT_DIR, &admin, PRIVATE_KEY
+    );
+    let private_key = hex::decode(remove_0x(read_file(private_key_path).unwrap().trim()))
+        .expect("invalid admin private key");
+
+    let pre_tx_hash = opts
+        .pre_tx_hash
+        .as_deref()
+        .or(chain_config.validators_tx_hash.as_deref())
+        .unwrap_or(PRE_HASH);
+    let pre_tx_hash = hex::decode(remove_0x(pre_tx_hash)).expect("invalid pre_tx_hash");
+
+    let mut output = Vec::new();
+    for validator in &validators {
+        output.extend_from_slice(&hex::decode(validator).unwrap());
+    }
+
+    let version = chain_config.system_config.version;
+    let transaction = encode_utxo_transaction(version, &pre_tx_hash, &output, LOCK_ID_VALIDATORS);
+    let tx_hash = crypto_hash(&transaction);
+    let signature = sign_message(&private_key, &tx_hash);
+    let sender = hex::decode(&admin).expect("invalid admin address");
+    let raw_tx = encode_raw_utxo_transaction(&transaction, &tx_hash, &signature, &sender);
+
+    let proposal = ValidatorsProposal {
+        validators: validators.clone(),
+        version,
+        lock_id: LOCK_ID_VALIDATORS,
+        pre_tx_hash: hex::encode(pre_tx_hash),
+        output: hex::encode(output),
+        tx_hash: hex::encode(tx_hash),
+        sender: admin,
+        signature: hex::encode(signature),
+        raw_tx: hex::encode(raw_tx),
+    };
+
+    let proposal_file = if opts.output.is_empty() {
+        VALIDATORS_PROPOSAL_FILE.to_string()
+    } else {
+        opts.output.clone()
+    };
+    write_toml(
+        &proposal,
+        format!(
+            "{}/{}/{}",
+            &opts.config_dir, &opts.chain_name, proposal_file
+        ),
+    );
+
+    // store chain_config
+    chain_config.set_pending_validators(validators, proposal.tx_hash.clone());
+    write_toml(&chain_config, file_name);
+
+    println!("tx_hash: 0x{}", &proposal.tx_hash);
+    println!("raw_tx: 0x{}", &proposal.raw_tx);
+
+    audit(
+        &opts.config_dir,
+        &opts.chain_name,
+        "propose-validators",
+        &before_hash,
+    );
+
+    Ok(proposal)
+}
+
+#[cfg(test)]
+mod propose_validators_test {
+    use super::*;
+
+    #[test]
+    fn encode_test() {
+        let mut buf = Vec::new();
+        encode_varint(300, &mut buf);
+        assert_eq!(buf, vec![0xac, 0x02]);
+
+        let tx = encode_utxo_transaction(0, &[0u8; 2], &[1u8, 2], LOCK_ID_VALIDATORS);
+        assert_eq!(
+            tx,
+            vec![0x12, 0x02, 0, 0, 0x1a, 0x02, 1, 2, 0x20, 0xec, 0x07]
+        );
+    }
+}
@@ -0,0 +1,188 @@
+// Copyright Rivtower Technologies LLC.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::config::chain_config::{ChainConfig, MicroService, NodeNetworkAddress};
+use crate::config::consensus_overlord::ConsensusOverlordKind;
+use crate::config::consensus_raft::ConsensusRaftKind;
+use crate::config::controller::ControllerKind;
+use crate::config::executor_chaincode::ExecutorChaincodeKind;
+use crate::config::executor_evm::ExecutorEvmKind;
+use crate::config::log_config::LogConfig;
+use crate::config::network_zenoh::NetworkZenohKind;
+use crate::config::node_config::NodeConfig;
+use crate::config::storage_opendal::StorageOpendalKind;
+use crate::constant::{
+    CONSENSUS, CONTROLLER, CONTROLLER_HSM, EXECUTOR, NETWORK, NODE_ADDRESS, STORAGE,
+    VALIDATOR_ADDRESS,
+};
+use crate::traits::MicroServiceKind;
+use std::collections::BTreeMap;
+
+/// every chain must have one micro service of each service
+pub const SERVICES: [&str; 5] = [NETWORK, CONSENSUS, EXECUTOR, STORAGE, CONTROLLER];
+
+/// everything about current node needed by micro service kinds
+pub struct NodeContext<'a> {
+    pub chain_name: &'a str,
+    pub domain: &'a str,
+    pub node_dir: &'a str,
+    pub chain_config: &'a ChainConfig,
+    pub node_config: &'a NodeConfig,
+    // network address of current node in chain_config
+    pub node: &'a NodeNetworkAddress,
+}
+
+impl<'a> NodeContext<'a> {
+    pub fn new(
+        chain_name: &'a str,
+        domain: &'a str,
+        node_dir: &'a str,
+        chain_config: &'a ChainConfig,
+        node_config: &'a NodeConfig,
+    ) -> Self {
+        let node = chain_config
+            .node_network_address_list
+            .iter()
+            .find(|node| node.domain == domain)
+            .expect("can't find domain in chain_config.node_network_address_list");
+        Self {
+            chain_name,
+            domain,
+            node_dir,
+            chain_config,
+            node_config,
+            node,
+        }
+    }
+
+    pub fn is_k8s(&self) -> bool {
+        !self.node.cluster.is_empty()
+    }
+
+    /// domain with chain name, also name of node svc in k8s
+    pub fn real_domain(&self) -> String {
+        format!("{}-{}", self.chain_name, self.domain)
+    }
+
+    pub fn internal_port(&self) -> u16 {
+        self.chain_config.internal_port(self.node)
+    }
+
+    /// account files are mounted into /mnt in k8s
+    pub fn node_address(&self) -> String {
+        if self.is_k8s() {
+            format!("/mnt/{NODE_ADDRESS}")
+        } else {
+            NODE_ADDRESS.to_string()
+        }
+    }
+
+    pub fn validator_address(&self) -> String {
+        if self.is_k8s() {
+            format!("/mnt/{VALIDATOR_ADDRESS}")
+        } else {
+            VALIDATOR_ADDRESS.to_string()
+        }
+    }
+
+    pub fn log_config(&self, service: &str) -> LogConfig {
+        self.node_config
+            .log_config(service, self.chain_name, self.domain)
+    }
+}
+
+/// micro service kinds keyed by image name
+pub struct MicroServiceRegistry {
+    kinds: BTreeMap<String, Box<dyn MicroServiceKind>>,
+}
+
+impl Default for MicroServiceRegistry {
+    fn default() -> Self {
+        let mut registry = Self {
+            kinds: BTreeMap::new(),
+        };
+        registry.register(Box::new(NetworkZenohKind));
+        registry.register(Box::new(ConsensusRaftKind));
+        registry.register(Box::new(ConsensusOverlordKind));
+        registry.register(Box::new(ExecutorEvmKind));
+        registry.register(Box::new(ExecutorChaincodeKind));
+        registry.register(Box::new(StorageOpendalKind));
+        registry.register(Box::new(ControllerKind(CONTROLLER)));
+        registry.register(Box::new(ControllerKind(CONTROLLER_HSM)));
+        registry
+    }
+}
+
+impl MicroServiceRegistry {
+    pub fn register(&mut self, kind: Box<dyn MicroServiceKind>) {
+        self.kinds.insert(kind.image().to_string(), kind);
+    }
+
+    pub fn get(&self, image: &str) -> Option<&dyn MicroServiceKind> {
+        self.kinds.get(image).map(|kind| kind.as_ref())
+    }
+
+    /// sections of config of all registered kinds
+    pub fn sections(&self) -> Vec<&str> {
+        self.kinds.values().map(|kind| kind.section()).collect()
+    }
+
+    /// micro services in chain and their kinds, in order of SERVICES
+    pub fn kinds<'a>(
+        &'a self,
+        chain_config: &'a ChainConfig,
+    ) -> Vec<(&'a MicroService, &'a dyn MicroServiceKind)> {
+        SERVICES
+            .iter()
+            .map(|service| {
+                let micro_service = chain_config
+                    .micro_service_list
+                    .iter()
+                    .find(|micro_service| {
+                        micro_service
+                            .role
+                            .is_some_and(|role| role.as_str() == *service)
+                    })
+                    .unwrap_or_else(|| panic!("unsupport {service} service"));
+                let kind = self
+                    .get(micro_service.kind())
+                    .unwrap_or_else(|| panic!("unknown micro service: {}", micro_service.kind()));
+                if kind.service() != *service {
+                    panic!(
+                        "micro service {} can't be used as {service}",
+                        micro_service.kind()
+                    );
+                }
+                (micro_service, kind)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod registry_test {
+    use super::*;
+    use crate::constant::NETWORK_ZENOH;
+
+    #[test]
+    fn registry_test() {
+        let registry = MicroServiceRegistry::default();
+        let kind = registry.get(NETWORK_ZENOH).unwrap();
+        assert_eq!(kind.service(), NETWORK);
+        assert_eq!(kind.section(), NETWORK_ZENOH);
+        assert_eq!(registry.get(CONTROLLER_HSM).unwrap().section(), CONTROLLER);
+        assert!(registry.get("network_p2p").is_none());
+        assert_eq!(registry.sections().len(), 8);
+    }
+}
@@ -0,0 +1,235 @@
+// Copyright Rivtower Technologies LLC.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::audit::{audit, chain_config_hash};
+use crate::config::chain_config::{ConfigStage, MicroService, ServiceRole};
+use crate::constant::{CHAIN_CONFIG_FILE, UPDATE_YAML_OPTS_FILE};
+use crate::error::Error;
+use crate::registry::MicroServiceRegistry;
+use crate::update_yaml::{execute_update_yaml, UpdateYamlOpts};
+use crate::util::{read_chain_config, read_file, write_toml};
+use clap::Parser;
+use std::fs;
+use std::path::Path;
+
+/// A subcommand for run
+#[derive(Parser, Debug, Clone)]
+pub struct SetImageOpts {
+    /// set chain name
+    #[clap(long = "chain-name", default_value = "test-chain")]
+    pub chain_name: String,
+    /// set config file directory, default means current directory
+    #[clap(long = "config-dir", default_value = ".")]
+    pub config_dir: String,
+    /// role of micro service: network/consensus/executor/storage/controller
+    #[clap(long = "role")]
+    pub role: String,
+    /// new image tag
+    #[clap(long = "tag")]
+    pub tag: Option<String>,
+    /// new image name
+    #[clap(long = "image")]
+    pub image: Option<String>,
+    /// implementation of image, set it when image is mirrored or custom built
+    #[clap(long = "kind")]
+    pub kind: Option<String>,
+    /// pin image by digest, like sha256:..., empty means unpin
+    #[clap(long = "digest")]
+    pub digest: Option<String>,
+    /// docker registry of this micro service, empty means default of update-yaml
+    #[clap(long = "registry")]
+    pub registry: Option<String>,
+    /// docker repo of this micro service, empty means default of update-yaml
+    #[clap(long = "repo")]
+    pub repo: Option<String>,
+    /// skip check of release line of micro services
+    #[clap(long = "skip-compat-check")]
+    pub skip_compat_check: bool,
+}
+
+fn non_empty(s: &str) -> Option<String> {
+    if s.is_empty() {
+        None
+    } else {
+        Some(s.to_string())
+    }
+}
+
+/// release line of tag looks like v6.7.4, which is (6, 7)
+/// tags like latest have no release line
+fn release_line(tag: &str) -> Option<(u64, u64)> {
+    let mut parts = tag.strip_prefix('v').unwrap_or(tag).split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+/// all micro services with versioned tag must share the same release line
+fn check_release_line(micro_service_list: &[MicroService]) -> Result<(), Error> {
+    let mut lines: Vec<(u64, u64)> = micro_service_list
+        .iter()
+        .filter_map(|micro_service| release_line(&micro_service.tag))
+        .collect();
+    lines.sort();
+    lines.dedup();
+    if lines.len() > 1 {
+        for micro_service in micro_service_list {
+            println!("{}:{}", &micro_service.image, &micro_service.tag);
+        }
+        println!(
+            "micro services are not in the same release line, use --skip-compat-check to ignore it"
+        );
+        return Err(Error::IncompatibleImages);
+    }
+    Ok(())
+}
+
+/// execute set image
+pub fn execute_set_image(opts: SetImageOpts) -> Result<(), Error> {
+    let before_hash = chain_config_hash(&opts.config_dir, &opts.chain_name);
+    // load chain_config
+    let file_name = format!(
+        "{}/{}/{}",
+        &opts.config_dir, &opts.chain_name, CHAIN_CONFIG_FILE
+    );
+    let mut chain_config = read_chain_config(&file_name).unwrap();
+    chain_config.check_genesis_hash()?;
+
+    let role: ServiceRole = opts.role.parse().map_err(|e| {
+        println!("{e}");
+        Error::InvalidMicroService
+    })?;
+
+    let stage = chain_config.stage.clone();
+    let micro_service = chain_config
+        .micro_service_list
+        .iter_mut()
+        .find(|micro_service| micro_service.role == Some(role))
+        .ok_or_else(|| {
+            println!("can't find {role} micro service");
+            Error::InvalidMicroService
+        })?;
+    let old_kind = micro_service.kind().to_string();
+
+    if let Some(image) = &opts.image {
+        micro_service.image = image.clone();
+    }
+    if let Some(kind) = &opts.kind {
+        micro_service.kind = non_empty(kind);
+    }
+    if let Some(tag) = &opts.tag {
+        // digest of old tag is useless
+        if tag != &micro_service.tag {
+            micro_service.digest = None;
+        }
+        micro_service.tag = tag.clone();
+    }
+    if let Some(digest) = &opts.digest {
+        micro_service.digest = non_empty(digest);
+    }
+    if let Some(registry) = &opts.registry {
+        micro_service.registry = non_empty(registry);
+    }
+    if let Some(repo) = &opts.repo {
+        micro_service.repo = non_empty(repo);
+    }
+
+    let registry = MicroServiceRegistry::default();
+    match registry.get(micro_service.kind()) {
+        Some(kind) if kind.service() == role.as_str() => {}
+        _ => {
+            println!(
+                "micro service {} can't be used as {role}",
+                micro_service.kind()
+            );
+            return Err(Error::InvalidMicroService);
+        }
+    }
+    // config of nodes depends on kind, only image can be changed after init
+    if micro_service.kind() != old_kind && stage != ConfigStage::Init {
+        println!("kind of {role} can only be changed in init stage");
+        return Err(Error::InvalidStage);
+    }
+
+    if !opts.skip_compat_check {
+        check_release_line(&chain_config.micro_service_list)?;
+    }
+
+    // store chain_config
+    write_toml(&chain_config, &file_name);
+
+    for node in &chain_config.node_network_address_list {
+        let node_dir = format!("{}/{}-{}", &opts.config_dir, &opts.chain_name, &node.domain);
+        if !Path::new(&node_dir).exists() {
+            continue;
+        }
+        let to = format!("{}/{}", &node_dir, CHAIN_CONFIG_FILE);
+        fs::copy(&file_name, to).unwrap();
+
+        // regenerate yaml with opts of last update-yaml
+        if !node.cluster.is_empty() {
+            let opts_file = format!("{}/{}", &node_dir, UPDATE_YAML_OPTS_FILE);
+            let Some(mut update_yaml_opts) = read_file(&opts_file)
+                .ok()
+                .and_then(|content| toml::from_str::<UpdateYamlOpts>(&content).ok())
+            else {
+                // default opts would render yaml with wrong storage and image settings
+                println!(
+                    "skip yaml of node {}: {opts_file} is missing or invalid, please run update-yaml first",
+                    &node.domain
+                );
+                continue;
+            };
+            update_yaml_opts.chain_name = opts.chain_name.clone();
+            update_yaml_opts.config_dir = opts.config_dir.clone();
+            update_yaml_opts.domain = node.domain.clone();
+            execute_update_yaml(update_yaml_opts)?;
+        }
+    }
+
+    audit(
+        &opts.config_dir,
+        &opts.chain_name,
+        "set-image",
+        &before_hash,
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod set_image_test {
+    use super::*;
+    use crate::config::chain_config::MicroServiceBuilder;
+
+    #[test]
+    fn release_line_test() {
+        assert_eq!(release_line("v6.7.4"), Some((6, 7)));
+        assert_eq!(release_line("6.7.0-rc1"), Some((6, 7)));
+        assert_eq!(release_line("latest"), None);
+
+        let mut micro_service_list: Vec<MicroService> = ["v6.7.4", "v6.7.0", "latest"]
+            .iter()
+            .map(|tag| {
+                MicroServiceBuilder::default()
+                    .image("network_zenoh".to_string())
+                    .tag(tag.to_string())
+                    .build()
+            })
+            .collect();
+        assert!(check_release_line(&micro_service_list).is_ok());
+        micro_service_list[0].tag = "v6.8.0".to_string();
+        assert!(check_release_line(&micro_service_list).is_err());
+    }
+}
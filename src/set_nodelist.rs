@@ -12,11 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::audit::{audit, chain_config_hash};
 use crate::config::chain_config::ConfigStage;
-use crate::config::chain_config::NodeNetworkAddressBuilder;
 use crate::constant::CHAIN_CONFIG_FILE;
 use crate::error::Error;
-use crate::util::{read_chain_config, write_toml};
+use crate::util::{load_node_list, read_chain_config, write_toml};
 use clap::Parser;
 
 /// A subcommand for run
@@ -32,60 +32,45 @@ pub struct SetNodeListOpts {
     /// for each node network address:
     /// k8s_cluster_name is optional, none means not k8s env.
     /// namespace is optional, none means default namespace.
-    #[clap(long = "nodelist")]
+    /// protocol prefix is optional, quic/tls/tcp, none means quic, like tcp/localhost:40000:node0
+    /// ipv6 host must be bracketed like [::1]:40000:node0
+    /// or use key=value like host=localhost;port=40000;domain=node0;cluster=k8s;namespace=default
+    #[clap(long = "nodelist", default_value = "")]
     pub node_list: String,
+    /// node list file in yaml or json, override --nodelist
+    #[clap(long = "nodelist-file")]
+    pub node_list_file: Option<String>,
 }
 
 /// execute set node list
 pub fn execute_set_nodelist(opts: SetNodeListOpts) -> Result<(), Error> {
+    let before_hash = chain_config_hash(&opts.config_dir, &opts.chain_name);
     // load chain_config
     let file_name = format!(
         "{}/{}/{}",
         &opts.config_dir, &opts.chain_name, CHAIN_CONFIG_FILE
     );
     let mut chain_config = read_chain_config(&file_name).unwrap();
+    chain_config.check_genesis_hash()?;
 
     // public and finalize is ok
     if chain_config.stage == ConfigStage::Init {
         return Err(Error::InvalidStage);
     }
 
-    let node_list_str: Vec<&str> = opts.node_list.split(',').collect();
-    let node_list = node_list_str
-        .iter()
-        .map(|node| {
-            let node_network_info: Vec<&str> = node.split(':').collect();
-            if node_network_info.len() == 3 {
-                NodeNetworkAddressBuilder::default()
-                    .host(node_network_info[0].to_string())
-                    .port(node_network_info[1].parse::<u16>().unwrap())
-                    .domain(node_network_info[2].to_string())
-                    .build()
-            } else if node_network_info.len() == 4 {
-                NodeNetworkAddressBuilder::default()
-                    .host(node_network_info[0].to_string())
-                    .port(node_network_info[1].parse::<u16>().unwrap())
-                    .domain(node_network_info[2].to_string())
-                    .cluster(node_network_info[3].to_string())
-                    .build()
-            } else if node_network_info.len() == 5 {
-                NodeNetworkAddressBuilder::default()
-                    .host(node_network_info[0].to_string())
-                    .port(node_network_info[1].parse::<u16>().unwrap())
-                    .domain(node_network_info[2].to_string())
-                    .cluster(node_network_info[3].to_string())
-                    .name_space(node_network_info[4].to_string())
-                    .build()
-            } else {
-                panic!("invalid node network address format!")
-            }
-        })
-        .collect();
+    let node_list = load_node_list(&opts.node_list, opts.node_list_file.as_deref())?;
 
     chain_config.set_node_network_address_list(node_list);
 
     // store chain_config
     write_toml(&chain_config, file_name);
 
+    audit(
+        &opts.config_dir,
+        &opts.chain_name,
+        "set-nodelist",
+        &before_hash,
+    );
+
     Ok(())
 }
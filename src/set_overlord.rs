@@ -0,0 +1,115 @@
+// Copyright Rivtower Technologies LLC.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::audit::{audit, chain_config_hash};
+use crate::constant::{CHAIN_CONFIG_FILE, CONSENSUS_OVERLORD};
+use crate::error::Error;
+use crate::update_node::{execute_update_node, UpdateNodeOpts};
+use crate::util::{find_micro_service, read_chain_config, write_toml};
+use clap::Parser;
+use std::fs;
+use std::path::Path;
+
+/// A subcommand for run
+#[derive(Parser, Debug, Clone)]
+pub struct SetOverlordOpts {
+    /// set chain name
+    #[clap(long = "chain-name", default_value = "test-chain")]
+    pub chain_name: String,
+    /// set config file directory, default means current directory
+    #[clap(long = "config-dir", default_value = ".")]
+    pub config_dir: String,
+    /// timeout of propose step in tenths of block interval, 0 means default of consensus_overlord
+    #[clap(long = "propose-ratio")]
+    pub propose_ratio: Option<u64>,
+    /// timeout of prevote step in tenths of block interval, 0 means default of consensus_overlord
+    #[clap(long = "prevote-ratio")]
+    pub prevote_ratio: Option<u64>,
+    /// timeout of precommit step in tenths of block interval, 0 means default of consensus_overlord
+    #[clap(long = "precommit-ratio")]
+    pub precommit_ratio: Option<u64>,
+    /// timeout of brake step in tenths of block interval, 0 means default of consensus_overlord
+    #[clap(long = "brake-ratio")]
+    pub brake_ratio: Option<u64>,
+    /// max timeout of a step in milliseconds, 0 means default of consensus_overlord
+    #[clap(long = "timeout-ms")]
+    pub timeout_ms: Option<u64>,
+}
+
+// 0 unsets the param
+fn merge_param(param: &mut Option<u64>, value: Option<u64>) {
+    match value {
+        Some(0) => *param = None,
+        Some(value) => *param = Some(value),
+        None => {}
+    }
+}
+
+/// execute set overlord
+pub fn execute_set_overlord(opts: SetOverlordOpts) -> Result<(), Error> {
+    let before_hash = chain_config_hash(&opts.config_dir, &opts.chain_name);
+    // load chain_config
+    let file_name = format!(
+        "{}/{}/{}",
+        &opts.config_dir, &opts.chain_name, CHAIN_CONFIG_FILE
+    );
+    let mut chain_config = read_chain_config(&file_name).unwrap();
+    chain_config.check_genesis_hash()?;
+
+    if !find_micro_service(&chain_config, CONSENSUS_OVERLORD) {
+        println!("consensus of chain is not {CONSENSUS_OVERLORD}");
+        return Err(Error::InvalidConsensusConfig);
+    }
+
+    let params = &mut chain_config.overlord;
+    merge_param(&mut params.propose_ratio, opts.propose_ratio);
+    merge_param(&mut params.prevote_ratio, opts.prevote_ratio);
+    merge_param(&mut params.precommit_ratio, opts.precommit_ratio);
+    merge_param(&mut params.brake_ratio, opts.brake_ratio);
+    merge_param(&mut params.timeout_ms, opts.timeout_ms);
+    if let Err(e) = params.check(chain_config.system_config.block_interval) {
+        println!("invalid overlord params: {e}");
+        return Err(Error::InvalidConsensusConfig);
+    }
+
+    // store chain_config
+    write_toml(&chain_config, &file_name);
+
+    // params must be the same on all nodes, update config of nodes in config dir
+    for node in &chain_config.node_network_address_list {
+        let node_dir = format!("{}/{}-{}", &opts.config_dir, &opts.chain_name, &node.domain);
+        if !Path::new(&node_dir).exists() {
+            continue;
+        }
+        let to = format!("{}/{}", &node_dir, CHAIN_CONFIG_FILE);
+        fs::copy(&file_name, to).unwrap();
+
+        execute_update_node(UpdateNodeOpts {
+            chain_name: opts.chain_name.clone(),
+            config_dir: opts.config_dir.clone(),
+            config_name: "config.toml".to_string(),
+            domain: node.domain.clone(),
+            force: false,
+        })?;
+    }
+
+    audit(
+        &opts.config_dir,
+        &opts.chain_name,
+        "set-overlord",
+        &before_hash,
+    );
+
+    Ok(())
+}
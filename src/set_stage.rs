@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::approve_config::count_approvals;
+use crate::audit::{audit, chain_config_hash};
 use crate::config::chain_config::ConfigStage;
 use crate::constant::CHAIN_CONFIG_FILE;
 use crate::error::Error;
@@ -34,19 +36,35 @@ pub struct SetStageOpts {
 
 /// execute set stage
 pub fn execute_set_stage(opts: SetStageOpts) -> Result<(), Error> {
+    let before_hash = chain_config_hash(&opts.config_dir, &opts.chain_name);
     // load chain_config
     let file_name = format!(
         "{}/{}/{}",
         &opts.config_dir, &opts.chain_name, CHAIN_CONFIG_FILE
     );
     let mut chain_config = read_chain_config(&file_name).unwrap();
+    chain_config.check_genesis_hash()?;
+
+    // genesis is fixed after finalize, can't go back to init/public
+    if chain_config.stage == ConfigStage::Finalize && opts.stage != "finalize" {
+        return Err(Error::InvalidStage);
+    }
 
     if opts.stage == "init" {
         chain_config.stage = ConfigStage::Init;
     } else if opts.stage == "public" {
         chain_config.stage = ConfigStage::Public;
     } else if opts.stage == "finalize" {
+        if chain_config.stage != ConfigStage::Finalize {
+            let approvals = count_approvals(&opts.config_dir, &opts.chain_name, &chain_config);
+            let quorum = chain_config.required_quorum();
+            if approvals < quorum {
+                println!("approvals: {approvals} quorum: {quorum}");
+                return Err(Error::InsufficientApprovals);
+            }
+        }
         chain_config.stage = ConfigStage::Finalize;
+        chain_config.genesis_hash = Some(chain_config.compute_genesis_hash());
     } else {
         return Err(Error::InvalidStage);
     }
@@ -54,5 +72,12 @@ pub fn execute_set_stage(opts: SetStageOpts) -> Result<(), Error> {
     // store chain_config
     write_toml(&chain_config, file_name);
 
+    audit(
+        &opts.config_dir,
+        &opts.chain_name,
+        "set-stage",
+        &before_hash,
+    );
+
     Ok(())
 }
@@ -0,0 +1,148 @@
+// Copyright Rivtower Technologies LLC.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::audit::{audit, chain_config_hash};
+use crate::config::chain_config::{ConfigStage, Topology};
+use crate::constant::CHAIN_CONFIG_FILE;
+use crate::error::Error;
+use crate::util::{read_chain_config, write_toml};
+use clap::Parser;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// A subcommand for run
+#[derive(Parser, Debug, Clone)]
+pub struct SetTopologyOpts {
+    /// set chain name
+    #[clap(long = "chain-name", default_value = "test-chain")]
+    pub chain_name: String,
+    /// set config file directory, default means current directory
+    #[clap(long = "config-dir", default_value = ".")]
+    pub config_dir: String,
+    /// topology type: full-mesh/explicit/ring/hub-and-spoke
+    #[clap(long = "type", default_value = "full-mesh")]
+    pub topology_type: String,
+    /// peers of explicit topology looks like node0=node1|node2,node1=node3
+    #[clap(long = "peers", default_value = "")]
+    pub peers: String,
+    /// number of nearest nodes on each side of ring topology
+    #[clap(long = "k", default_value = "1")]
+    pub k: usize,
+    /// hub nodes of hub-and-spoke topology splited by ','
+    #[clap(long = "hubs", default_value = "")]
+    pub hubs: String,
+}
+
+/// execute set topology
+pub fn execute_set_topology(opts: SetTopologyOpts) -> Result<(), Error> {
+    let before_hash = chain_config_hash(&opts.config_dir, &opts.chain_name);
+    // load chain_config
+    let file_name = format!(
+        "{}/{}/{}",
+        &opts.config_dir, &opts.chain_name, CHAIN_CONFIG_FILE
+    );
+    let mut chain_config = read_chain_config(&file_name).unwrap();
+    chain_config.check_genesis_hash()?;
+
+    // public and finalize is ok
+    if chain_config.stage == ConfigStage::Init {
+        return Err(Error::InvalidStage);
+    }
+
+    let topology = match opts.topology_type.as_str() {
+        "full-mesh" => Topology::FullMesh,
+        "explicit" => {
+            let mut peers = BTreeMap::new();
+            for item in opts.peers.split(',').filter(|s| !s.is_empty()) {
+                let (domain, node_peers) = item.split_once('=').ok_or_else(|| {
+                    println!("invalid peers item: {item}");
+                    Error::InvalidTopology
+                })?;
+                peers.insert(
+                    domain.to_string(),
+                    node_peers.split('|').map(|s| s.to_string()).collect(),
+                );
+            }
+            Topology::Explicit { peers }
+        }
+        "ring" => {
+            if opts.k == 0 {
+                println!("k of ring topology must be greater than 0");
+                return Err(Error::InvalidTopology);
+            }
+            Topology::Ring { k: opts.k }
+        }
+        "hub-and-spoke" => {
+            let hubs: Vec<String> = opts
+                .hubs
+                .split(',')
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string())
+                .collect();
+            if hubs.is_empty() {
+                println!("hub-and-spoke topology need at least one hub");
+                return Err(Error::InvalidTopology);
+            }
+            Topology::HubAndSpoke { hubs }
+        }
+        _ => {
+            println!("unknown topology type: {}", &opts.topology_type);
+            return Err(Error::InvalidTopology);
+        }
+    };
+
+    // domains referenced by topology must be in node list
+    let domains: Vec<&String> = match &topology {
+        Topology::Explicit { peers } => peers
+            .iter()
+            .flat_map(|(domain, peers)| std::iter::once(domain).chain(peers.iter()))
+            .collect(),
+        Topology::HubAndSpoke { hubs } => hubs.iter().collect(),
+        _ => Vec::new(),
+    };
+    for domain in domains {
+        if !chain_config
+            .node_network_address_list
+            .iter()
+            .any(|n| &n.domain == domain)
+        {
+            println!("unknown node: {domain}");
+            return Err(Error::InvalidTopology);
+        }
+    }
+
+    chain_config.topology = topology;
+
+    // store chain_config
+    write_toml(&chain_config, &file_name);
+
+    // update-node reads chain_config in node dir
+    for node in &chain_config.node_network_address_list {
+        let node_dir = format!("{}/{}-{}", &opts.config_dir, &opts.chain_name, &node.domain);
+        if Path::new(&node_dir).exists() {
+            let to = format!("{}/{}", &node_dir, CHAIN_CONFIG_FILE);
+            fs::copy(&file_name, to).unwrap();
+        }
+    }
+
+    audit(
+        &opts.config_dir,
+        &opts.chain_name,
+        "set-topology",
+        &before_hash,
+    );
+
+    Ok(())
+}
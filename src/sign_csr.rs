@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::audit::{audit, chain_config_hash};
 use crate::constant::{CA_CERT_DIR, CERTS_DIR, CERT_PEM, CSR_PEM, KEY_PEM};
 use crate::error::Error;
 use crate::util::{read_file, sign_csr, write_file};
@@ -33,6 +34,7 @@ pub struct SignCSROpts {
 
 /// execute sign csr
 pub fn execute_sign_csr(opts: SignCSROpts) -> Result<String, Error> {
+    let before_hash = chain_config_hash(&opts.config_dir, &opts.chain_name);
     // load ca cert
     let ca_cert_path = format!(
         "{}/{}/{}/{}",
@@ -62,5 +64,7 @@ pub fn execute_sign_csr(opts: SignCSROpts) -> Result<String, Error> {
     );
     write_file(cert_pem.as_bytes(), cert_pem_path);
 
+    audit(&opts.config_dir, &opts.chain_name, "sign-csr", &before_hash);
+
     Ok(cert_pem)
 }
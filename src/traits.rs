@@ -12,7 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::config::node_config::GrpcPorts;
+use crate::constant::{CONSENSUS, CONTROLLER, EXECUTOR, NETWORK, STORAGE};
+use crate::error::Error;
+use crate::registry::NodeContext;
 use crate::util;
+use k8s_openapi::api::core::v1::{ContainerPort, EnvVar, ExecAction, Probe};
 use serde::Serialize;
 use std::path;
 
@@ -26,3 +31,82 @@ pub trait TomlWriter {
 
     fn section(&self) -> String;
 }
+
+/// allocate grpc and metrics ports for local node
+/// returns first port of grpc ports and metrics ports
+pub trait PortAllocator {
+    fn allocate(&mut self, domain: &str) -> (u16, u16);
+    /// allocate a single port from base for allocated node, like chaincode port
+    fn allocate_port(&mut self, domain: &str, base: u16) -> u16;
+}
+
+/// a kind of micro service, registered in MicroServiceRegistry by image name
+pub trait MicroServiceKind {
+    /// image name of micro service, like network_zenoh
+    fn image(&self) -> &str;
+
+    /// network/consensus/executor/storage/controller, also used as name of container
+    fn service(&self) -> &str;
+
+    /// section of config in config.toml
+    fn section(&self) -> &str;
+
+    /// render config of micro service into config file
+    fn render(&self, ctx: &NodeContext, config_file: &str) -> Result<(), Error>;
+
+    /// command of container
+    fn command(&self) -> Vec<String>;
+
+    fn grpc_port(&self, grpc_ports: &GrpcPorts) -> u16 {
+        match self.service() {
+            NETWORK => grpc_ports.network_port,
+            CONSENSUS => grpc_ports.consensus_port,
+            EXECUTOR => grpc_ports.executor_port,
+            STORAGE => grpc_ports.storage_port,
+            CONTROLLER => grpc_ports.controller_port,
+            service => panic!("unknown service: {service}"),
+        }
+    }
+
+    /// ports of container
+    fn container_ports(&self, ctx: &NodeContext) -> Vec<ContainerPort> {
+        vec![ContainerPort {
+            container_port: self.grpc_port(&ctx.node_config.grpc_ports) as i32,
+            name: Some("grpc".to_string()),
+            protocol: Some("TCP".to_string()),
+            ..Default::default()
+        }]
+    }
+
+    /// liveness probe of container
+    fn liveness_probe(&self, ctx: &NodeContext) -> Probe {
+        Probe {
+            exec: Some(ExecAction {
+                command: Some(vec![
+                    "grpc-health-probe".to_string(),
+                    format!(
+                        "-addr=localhost:{}",
+                        self.grpc_port(&ctx.node_config.grpc_ports)
+                    ),
+                ]),
+            }),
+            initial_delay_seconds: Some(self.probe_initial_delay_seconds()),
+            period_seconds: Some(10),
+            ..Default::default()
+        }
+    }
+
+    fn probe_initial_delay_seconds(&self) -> i32 {
+        30
+    }
+
+    /// whether account of node is mounted into container
+    fn mount_account(&self) -> bool {
+        true
+    }
+
+    /// env of container
+    fn env(&self, _ctx: &NodeContext) -> Vec<EnvVar> {
+        vec![]
+    }
+}
@@ -12,25 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::config::consensus_overlord::ConsensusOverlord;
-use crate::config::consensus_raft::Consensus as RAFT_Consensus;
-use crate::config::controller::ControllerConfig;
-use crate::config::executor_evm::ExecutorEvmConfig;
-use crate::config::log_config::LogConfig;
-use crate::config::network_zenoh::{ModuleConfig, PeerConfig as ZenohPeerConfig, ZenohConfig};
-use crate::config::storage_opendal::StorageOpendalConfig;
+use crate::audit::{audit, chain_config_hash};
+use crate::config_merge::merge_rendered;
 use crate::constant::{
-    ACCOUNT_DIR, CA_CERT_DIR, CERTS_DIR, CERT_PEM, CHAIN_CONFIG_FILE, CONSENSUS,
-    CONSENSUS_OVERLORD, CONSENSUS_RAFT, CONTROLLER, CONTROLLER_HSM, EXECUTOR, EXECUTOR_EVM,
-    KEY_PEM, NETWORK, NETWORK_ZENOH, NODE_ADDRESS, NODE_CONFIG_FILE, PRIVATE_KEY, STORAGE,
-    STORAGE_OPENDAL, VALIDATOR_ADDRESS,
+    ACCOUNT_DIR, CHAIN_CONFIG_FILE, NODE_ADDRESS, NODE_CONFIG_FILE, PRIVATE_KEY, VALIDATOR_ADDRESS,
 };
 use crate::error::Error;
-use crate::traits::TomlWriter;
-use crate::util::{find_micro_service, read_chain_config, read_file, read_node_config};
+use crate::overrides::apply_overrides;
+use crate::registry::{MicroServiceRegistry, NodeContext};
+use crate::util::{read_chain_config, read_node_config};
 use clap::Parser;
 use std::fs;
-use std::net::{Ipv4Addr, Ipv6Addr};
 
 /// A subcommand for run
 #[derive(Parser, Debug, Clone)]
@@ -47,42 +39,41 @@ pub struct UpdateNodeOpts {
     /// domain of node
     #[clap(long = "domain")]
     pub domain: String,
+    /// overwrite edits on node which conflict with rendered config
+    #[clap(long = "force")]
+    pub force: bool,
 }
 
 /// generate node config files by chain_config and node_config
 pub fn execute_update_node(opts: UpdateNodeOpts) -> Result<(), Error> {
+    let before_hash = chain_config_hash(&opts.config_dir, &opts.chain_name);
     let node_dir = format!("{}/{}-{}", &opts.config_dir, &opts.chain_name, &opts.domain);
 
     // load node_config
     let file_name = format!("{}/{}", &node_dir, NODE_CONFIG_FILE);
     let node_config = read_node_config(file_name).unwrap();
+    node_config.check_log_config()?;
 
     // load chain_config
     let file_name = format!("{}/{}", &node_dir, CHAIN_CONFIG_FILE);
     let chain_config = read_chain_config(file_name).unwrap();
+    chain_config.check_genesis_hash()?;
 
-    let mut my_cluster_name = "";
-    let mut my_external_port = 0;
-    let mut my_name_space = "";
-    for node_network_address in &chain_config.node_network_address_list {
-        if node_network_address.domain == opts.domain {
-            my_cluster_name = &node_network_address.cluster;
-            my_external_port = node_network_address.port;
-            my_name_space = &node_network_address.name_space;
-        }
-    }
-
-    if my_external_port == 0 {
-        panic!("can't find domain in chain_config.node_network_address_list");
-    }
-
-    let is_k8s = !my_cluster_name.is_empty();
+    let ctx = NodeContext::new(
+        &opts.chain_name,
+        &opts.domain,
+        &node_dir,
+        &chain_config,
+        &node_config,
+    );
 
     // because this file write by one and one section
     // so write mode must be append
     // so if you want rewrite, delete old config file at first
+    // render into a temp file, which is merged into config file at last
     let config_file_name = format!("{}/{}", &node_dir, opts.config_name);
-    let _ = fs::remove_file(&config_file_name);
+    let render_file_name = format!("{config_file_name}.new");
+    let _ = fs::remove_file(&render_file_name);
 
     // copy account files
     {
@@ -108,250 +99,28 @@ pub fn execute_update_node(opts: UpdateNodeOpts) -> Result<(), Error> {
         fs::copy(from, to).unwrap();
     }
 
-    let real_domain = format!("{}-{}", &opts.chain_name, &opts.domain);
-
-    // network config file
-    // config peers
-    // if current node in k8s
-    // -- if same cluster and same namespace port is 40000, domain is svc name
-    // -- if same cluster and different namespace, port is 40000, domain is svc name
-    // -- if diffrent cluster and peer host is FQDN, port is peer port, domain is svc name
-    // -- if diffrent cluster and peer host is ip, port is 40000, domain is svc name
-    // if current node not in k8s, port is peer port, domain is peer host
-    if find_micro_service(&chain_config, NETWORK_ZENOH) {
-        let mut zenoh_peers: Vec<ZenohPeerConfig> = Vec::new();
-        for node_network_address in &chain_config.node_network_address_list {
-            if node_network_address.domain != opts.domain {
-                if is_k8s {
-                    let peer_cluster_name = &node_network_address.cluster;
-                    let peer_name_space = &node_network_address.name_space;
-                    let peer_host = &node_network_address.host;
-                    let is_peer_host_ip = peer_host.parse::<Ipv4Addr>().is_ok()
-                        || peer_host.parse::<Ipv6Addr>().is_ok();
-                    let peer_port = node_network_address.port;
-                    let peer_svc_name =
-                        format!("{}-{}", &opts.chain_name, &node_network_address.domain);
-
-                    let same_cluster = peer_cluster_name == my_cluster_name;
-                    let same_name_space = peer_name_space == my_name_space;
-
-                    let peer_config = match (same_cluster, same_name_space, is_peer_host_ip) {
-                        (true, true, _) => ZenohPeerConfig {
-                            port: 40000,
-                            domain: peer_svc_name,
-                            protocol: "quic".to_string(),
-                        },
-                        (true, false, _) => ZenohPeerConfig {
-                            port: 40000,
-                            domain: peer_svc_name,
-                            protocol: "quic".to_string(),
-                        },
-                        (false, _, false) => ZenohPeerConfig {
-                            port: peer_port,
-                            domain: peer_svc_name,
-                            protocol: "quic".to_string(),
-                        },
-                        (false, _, true) => ZenohPeerConfig {
-                            port: 40000,
-                            domain: peer_svc_name,
-                            protocol: "quic".to_string(),
-                        },
-                    };
-
-                    zenoh_peers.push(peer_config);
-                } else {
-                    zenoh_peers.push(ZenohPeerConfig {
-                        port: node_network_address.port,
-                        domain: node_network_address.host.clone(),
-                        protocol: "quic".to_string(),
-                    });
-                }
-            }
-        }
-        // load cert
-        let ca_cert = read_file(format!("{}/{}/{}", &node_dir, CA_CERT_DIR, CERT_PEM)).unwrap();
-        let cert = read_file(format!(
-            "{}/{}/{}/{}",
-            &node_dir, CERTS_DIR, &opts.domain, CERT_PEM
-        ))
-        .unwrap();
-        let key = read_file(format!(
-            "{}/{}/{}/{}",
-            &node_dir, CERTS_DIR, &opts.domain, KEY_PEM
-        ))
-        .unwrap();
-
-        // modules
-        let modules = vec![
-            ModuleConfig {
-                module_name: "consensus".to_string(),
-                hostname: "localhost".to_string(),
-                port: node_config.grpc_ports.consensus_port,
-            },
-            ModuleConfig {
-                module_name: "controller".to_string(),
-                hostname: "localhost".to_string(),
-                port: node_config.grpc_ports.controller_port,
-            },
-        ];
-
-        let network_config = ZenohConfig {
-            port: if is_k8s { 40000 } else { my_external_port },
-            grpc_port: node_config.grpc_ports.network_port,
-            ca_cert,
-            cert,
-            priv_key: key,
-            peers: zenoh_peers,
-            domain: real_domain.clone(),
-            protocol: "quic".to_string(),
-            node_address: if is_k8s {
-                format!("/mnt/{NODE_ADDRESS}")
-            } else {
-                NODE_ADDRESS.to_string()
-            },
-            validator_address: if is_k8s {
-                format!("/mnt/{VALIDATOR_ADDRESS}")
-            } else {
-                VALIDATOR_ADDRESS.to_string()
-            },
-            chain_id: chain_config.system_config.chain_id.clone(),
-            modules,
-            metrics_port: node_config.metrics_ports.network_metrics_port,
-            enable_metrics: node_config.enable_metrics,
-            log_config: LogConfig {
-                max_level: node_config.log_level.clone(),
-                service_name: NETWORK.to_owned(),
-                rolling_file_path: node_config.log_file_path.clone(),
-                agent_endpoint: node_config.jaeger_agent_endpoint.clone(),
-                filter: node_config.log_level.clone(),
-            },
-        };
-        network_config.write(&config_file_name);
-    } else {
-        panic!("unsupport network service");
-    }
-
-    // consensus config file
-    // if consensus_raft
-    if find_micro_service(&chain_config, CONSENSUS_RAFT) {
-        let consensus_config = RAFT_Consensus::new(
-            node_config.grpc_ports.network_port,
-            node_config.grpc_ports.controller_port,
-            if is_k8s {
-                format!("/mnt/{NODE_ADDRESS}")
-            } else {
-                NODE_ADDRESS.to_string()
-            },
-            node_config.grpc_ports.consensus_port,
-            node_config.metrics_ports.consensus_metrics_port,
-            node_config.enable_metrics,
-            node_config.log_level.clone(),
-        );
-        consensus_config.write(&config_file_name);
-    } else if find_micro_service(&chain_config, CONSENSUS_OVERLORD) {
-        let consensus_config = ConsensusOverlord::new(
-            real_domain.clone(),
-            node_config.grpc_ports.controller_port,
-            node_config.grpc_ports.consensus_port,
-            node_config.grpc_ports.network_port,
-            node_config.metrics_ports.consensus_metrics_port,
-            node_config.enable_metrics,
-            LogConfig {
-                max_level: node_config.log_level.clone(),
-                service_name: CONSENSUS.to_owned(),
-                rolling_file_path: node_config.log_file_path.clone(),
-                agent_endpoint: node_config.jaeger_agent_endpoint.clone(),
-                filter: node_config.log_level.clone(),
-            },
-        );
-        consensus_config.write(&config_file_name);
-    } else {
-        panic!("unsupport consensus service");
-    }
-
-    // executor config file
-    // if executor_evm
-    if find_micro_service(&chain_config, EXECUTOR_EVM) {
-        let executor_config = ExecutorEvmConfig::new(
-            real_domain.clone(),
-            node_config.grpc_ports.executor_port,
-            node_config.metrics_ports.executor_metrics_port,
-            node_config.enable_metrics,
-            LogConfig {
-                max_level: node_config.log_level.clone(),
-                service_name: EXECUTOR.to_owned(),
-                rolling_file_path: node_config.log_file_path.clone(),
-                agent_endpoint: node_config.jaeger_agent_endpoint.clone(),
-                filter: node_config.log_level.clone(),
-            },
-        );
-        executor_config.write(&config_file_name);
-    } else {
-        panic!("unsupport executor service");
-    }
-
-    // storage config file
-    // if storage_opendal
-    if find_micro_service(&chain_config, STORAGE_OPENDAL) {
-        let storage_config = StorageOpendalConfig::new(
-            real_domain.clone(),
-            node_config.grpc_ports.storage_port,
-            node_config.metrics_ports.storage_metrics_port,
-            node_config.enable_metrics,
-            LogConfig {
-                max_level: node_config.log_level.clone(),
-                service_name: STORAGE.to_owned(),
-                rolling_file_path: node_config.log_file_path.clone(),
-                agent_endpoint: node_config.jaeger_agent_endpoint.clone(),
-                filter: node_config.log_level.clone(),
-            },
-            node_config.cloud_storage.clone(),
-            node_config.exporter.clone(),
-        );
-        storage_config.write(&config_file_name);
-    } else {
-        panic!("unsupport storage service");
+    // config of micro services
+    let registry = MicroServiceRegistry::default();
+    for (_, kind) in registry.kinds(&chain_config) {
+        kind.render(&ctx, &render_file_name)?;
     }
 
-    // controller config file
-    if find_micro_service(&chain_config, CONTROLLER)
-        || find_micro_service(&chain_config, CONTROLLER_HSM)
-    {
-        chain_config.genesis_block.write(&config_file_name);
-        chain_config.system_config.write(&config_file_name);
-        let controller_config = ControllerConfig {
-            domain: real_domain,
-            network_port: node_config.grpc_ports.network_port,
-            consensus_port: node_config.grpc_ports.consensus_port,
-            executor_port: node_config.grpc_ports.executor_port,
-            storage_port: node_config.grpc_ports.storage_port,
-            controller_port: node_config.grpc_ports.controller_port,
-            node_address: if is_k8s {
-                format!("/mnt/{NODE_ADDRESS}")
-            } else {
-                NODE_ADDRESS.to_string()
-            },
-            validator_address: if is_k8s {
-                format!("/mnt/{VALIDATOR_ADDRESS}")
-            } else {
-                VALIDATOR_ADDRESS.to_string()
-            },
-            metrics_port: node_config.metrics_ports.controller_metrics_port,
-            enable_metrics: node_config.enable_metrics,
-            log_config: LogConfig {
-                max_level: node_config.log_level.clone(),
-                service_name: CONTROLLER.to_owned(),
-                rolling_file_path: node_config.log_file_path.clone(),
-                agent_endpoint: node_config.jaeger_agent_endpoint.clone(),
-                filter: node_config.log_level.clone(),
-            },
-            is_danger: node_config.is_danger,
-            tx_persistence: node_config.enable_tx_persistence,
-        };
-        controller_config.write(&config_file_name);
-    } else {
-        panic!("unsupport controller service");
-    }
+    // overrides of chain and node
+    apply_overrides(
+        &format!("{}/{}", &opts.config_dir, &opts.chain_name),
+        &node_dir,
+        &render_file_name,
+    )?;
+
+    // keep edits on node which not conflict with rendered config
+    merge_rendered(&config_file_name, &render_file_name, opts.force)?;
+
+    audit(
+        &opts.config_dir,
+        &opts.chain_name,
+        "update-node",
+        &before_hash,
+    );
 
     Ok(())
 }
@@ -12,22 +12,27 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::audit::{audit, chain_config_hash};
+use crate::config::chain_config::{NetworkProtocol, NodeRole};
 use crate::constant::{
-    CHAIN_CONFIG_FILE, CONSENSUS_OVERLORD, CONSENSUS_RAFT, CONTROLLER, CONTROLLER_HSM,
-    EXECUTOR_EVM, NETWORK_ZENOH, NODE_CONFIG_FILE, PRIVATE_KEY, STORAGE_OPENDAL, VALIDATOR_ADDRESS,
+    CHAIN_CONFIG_FILE, NODE_CONFIG_FILE, PRIVATE_KEY, STORAGE, UPDATE_YAML_OPTS_FILE,
+    VALIDATOR_ADDRESS,
 };
 use crate::error::Error;
-use crate::util::{read_chain_config, read_file, read_node_config, svc_name, write_file};
+use crate::registry::{MicroServiceRegistry, NodeContext};
+use crate::util::{
+    read_chain_config, read_file, read_node_config, svc_name, write_file, write_toml,
+};
 use clap::Parser;
 use k8s_openapi::{
     api::{
         apps::v1::{StatefulSet, StatefulSetSpec},
         core::v1::{
-            Affinity, ConfigMap, ConfigMapVolumeSource, Container, ContainerPort, ExecAction,
+            Affinity, ConfigMap, ConfigMapVolumeSource, Container, EnvFromSource,
             HostPathVolumeSource, PersistentVolumeClaim, PersistentVolumeClaimSpec,
-            PodAffinityTerm, PodAntiAffinity, PodSecurityContext, PodSpec, PodTemplateSpec, Probe,
-            ResourceRequirements, Service, ServicePort, ServiceSpec, Volume, VolumeMount,
-            WeightedPodAffinityTerm,
+            PodAffinityTerm, PodAntiAffinity, PodSecurityContext, PodSpec, PodTemplateSpec,
+            ResourceRequirements, SecretEnvSource, Service, ServicePort, ServiceSpec, Volume,
+            VolumeMount, WeightedPodAffinityTerm,
         },
         discovery::v1::{Endpoint, EndpointConditions, EndpointPort, EndpointSlice},
     },
@@ -100,6 +105,9 @@ pub struct UpdateYamlOpts {
     /// is gen kustomization
     #[clap(long = "enable-kustomize")]
     pub enable_kustomize: bool,
+    /// k8s secret loaded into env of storage container, provides credentials referred by env:NAME
+    #[clap(long = "storage-env-secret")]
+    pub storage_env_secret: Option<String>,
 }
 
 impl Default for UpdateYamlOpts {
@@ -122,6 +130,7 @@ impl Default for UpdateYamlOpts {
             enable_debug: false,
             disable_health_check: false,
             enable_kustomize: false,
+            storage_env_secret: None,
         }
     }
 }
@@ -138,6 +147,7 @@ pub struct NodeK8sConfig {
 
 /// generate k8s yaml by chain_config and node_config
 pub fn execute_update_yaml(opts: UpdateYamlOpts) -> Result<NodeK8sConfig, Error> {
+    let before_hash = chain_config_hash(&opts.config_dir, &opts.chain_name);
     let mut node_k8s_config = NodeK8sConfig::default();
 
     let node_name = format!("{}-{}", &opts.chain_name, &opts.domain);
@@ -150,16 +160,23 @@ pub fn execute_update_yaml(opts: UpdateYamlOpts) -> Result<NodeK8sConfig, Error>
     // load chain_config
     let file_name = format!("{}/{}", &node_dir, CHAIN_CONFIG_FILE);
     let chain_config = read_chain_config(file_name).unwrap();
+    chain_config.check_genesis_hash()?;
 
     // check current node is k8s or not
     let mut my_cluster_name = "";
     let mut my_external_port = 0;
     let mut my_name_space = "";
+    let mut my_protocol = NetworkProtocol::default();
+    let mut my_internal_port = chain_config.internal_port;
+    let mut my_role = NodeRole::default();
     for node_network_address in &chain_config.node_network_address_list {
         if node_network_address.domain == opts.domain {
+            my_role = node_network_address.role;
+            my_internal_port = chain_config.internal_port(node_network_address);
             my_cluster_name = &node_network_address.cluster;
             my_external_port = node_network_address.port;
             my_name_space = &node_network_address.name_space;
+            my_protocol = node_network_address.protocol;
         }
     }
 
@@ -177,8 +194,8 @@ pub fn execute_update_yaml(opts: UpdateYamlOpts) -> Result<NodeK8sConfig, Error>
     let yamls_path = format!("{}/yamls", &node_dir);
     fs::create_dir_all(&yamls_path).unwrap();
 
-    // protocol now only support netowrk_zenoh which use quic
-    let network_protocol = "UDP";
+    // quic use UDP, tls and tcp use TCP
+    let network_protocol = my_protocol.k8s_protocol();
 
     // update yaml
     // node svc
@@ -241,7 +258,7 @@ pub fn execute_update_yaml(opts: UpdateYamlOpts) -> Result<NodeK8sConfig, Error>
             ..Default::default()
         };
         let mut data = BTreeMap::new();
-        data.insert("node_address".to_string(), node_config.account);
+        data.insert("node_address".to_string(), node_config.account.clone());
         data.insert(
             "validator_address".to_string(),
             fs::read_to_string(format!("{}/{}", &node_dir, VALIDATOR_ADDRESS)).unwrap(),
@@ -277,6 +294,10 @@ pub fn execute_update_yaml(opts: UpdateYamlOpts) -> Result<NodeK8sConfig, Error>
             "app.kubernetes.io/chain-node".to_string(),
             node_name.clone(),
         );
+        labels.insert(
+            "app.kubernetes.io/chain-node-role".to_string(),
+            my_role.to_string(),
+        );
         metadata.labels = Some(labels);
 
         let mut statefulset = StatefulSet {
@@ -341,6 +362,10 @@ pub fn execute_update_yaml(opts: UpdateYamlOpts) -> Result<NodeK8sConfig, Error>
             "app.kubernetes.io/chain-node".to_string(),
             node_name.clone(),
         );
+        labels.insert(
+            "app.kubernetes.io/chain-node-role".to_string(),
+            my_role.to_string(),
+        );
         metadata.labels = Some(labels);
         template.metadata = Some(metadata);
 
@@ -388,25 +413,17 @@ pub fn execute_update_yaml(opts: UpdateYamlOpts) -> Result<NodeK8sConfig, Error>
         limits.insert("memory".to_string(), Quantity(opts.limits_memory.clone()));
         container_resources_requirements.limits = Some(limits);
 
-        // network
-        let mut network_container = Container {
-            name: "network".to_string(),
-            image_pull_policy: Some(opts.pull_policy.clone()),
-            ports: Some(vec![
-                ContainerPort {
-                    container_port: 40000,
-                    name: Some("network".to_string()),
-                    protocol: Some(network_protocol.to_string()),
-                    ..Default::default()
-                },
-                ContainerPort {
-                    container_port: 50000,
-                    name: Some("grpc".to_string()),
-                    protocol: Some("TCP".to_string()),
-                    ..Default::default()
-                },
-            ]),
-            volume_mounts: Some(vec![
+        // containers of micro services
+        let ctx = NodeContext::new(
+            &opts.chain_name,
+            &opts.domain,
+            &node_dir,
+            &chain_config,
+            &node_config,
+        );
+        let registry = MicroServiceRegistry::default();
+        for (micro_service, kind) in registry.kinds(&chain_config) {
+            let mut volume_mounts = vec![
                 VolumeMount {
                     mount_path: "/data".to_string(),
                     name: "datadir".to_string(),
@@ -417,374 +434,52 @@ pub fn execute_update_yaml(opts: UpdateYamlOpts) -> Result<NodeK8sConfig, Error>
                     name: "node-config".to_string(),
                     ..Default::default()
                 },
-                VolumeMount {
+            ];
+            if kind.mount_account() {
+                volume_mounts.push(VolumeMount {
                     mount_path: "/mnt".to_string(),
                     name: "node-account".to_string(),
                     ..Default::default()
-                },
-                VolumeMount {
-                    mount_path: "/etc/localtime".to_string(),
-                    name: "node-localtime".to_string(),
-                    ..Default::default()
-                },
-            ]),
-            working_dir: Some("/data".to_string()),
-            liveness_probe: if opts.disable_health_check {
-                None
-            } else {
-                Some(Probe {
-                    exec: Some(ExecAction {
-                        command: Some(vec![
-                            "grpc-health-probe".to_string(),
-                            "-addr=localhost:50000".to_string(),
-                        ]),
-                    }),
-                    initial_delay_seconds: Some(30),
-                    period_seconds: Some(10),
-                    ..Default::default()
-                })
-            },
-            resources: Some(container_resources_requirements.clone()),
-            ..Default::default()
-        };
-
-        for micro_service in &chain_config.micro_service_list {
-            if micro_service.image.starts_with("network") {
-                if micro_service.image == NETWORK_ZENOH {
-                    network_container.image = Some(format!(
-                        "{}/{}/{}:{}",
-                        &opts.docker_registry,
-                        &opts.docker_repo,
-                        &micro_service.image,
-                        &micro_service.tag
-                    ));
-                    network_container.command = Some(vec![
-                        "network".to_string(),
-                        "run".to_string(),
-                        "-c".to_string(),
-                        "/etc/cita-cloud/config/config.toml".to_string(),
-                    ]);
-                } else {
-                    panic!("Unknown network service!");
-                }
+                });
             }
-        }
-
-        containers.push(network_container);
-
-        // consensus
-        let mut consensus_container = Container {
-            name: "consensus".to_string(),
-            image_pull_policy: Some(opts.pull_policy.clone()),
-            ports: Some(vec![ContainerPort {
-                container_port: 50001,
-                name: Some("grpc".to_string()),
-                protocol: Some("TCP".to_string()),
+            volume_mounts.push(VolumeMount {
+                mount_path: "/etc/localtime".to_string(),
+                name: "node-localtime".to_string(),
                 ..Default::default()
-            }]),
-            volume_mounts: Some(vec![
-                VolumeMount {
-                    mount_path: "/data".to_string(),
-                    name: "datadir".to_string(),
-                    ..Default::default()
-                },
-                VolumeMount {
-                    mount_path: "/etc/cita-cloud/config".to_string(),
-                    name: "node-config".to_string(),
-                    ..Default::default()
-                },
-                VolumeMount {
-                    mount_path: "/mnt".to_string(),
-                    name: "node-account".to_string(),
-                    ..Default::default()
-                },
-                VolumeMount {
-                    mount_path: "/etc/localtime".to_string(),
-                    name: "node-localtime".to_string(),
-                    ..Default::default()
-                },
-            ]),
-            working_dir: Some("/data".to_string()),
-            liveness_probe: if opts.disable_health_check {
-                None
-            } else {
-                Some(Probe {
-                    exec: Some(ExecAction {
-                        command: Some(vec![
-                            "grpc-health-probe".to_string(),
-                            "-addr=localhost:50001".to_string(),
-                        ]),
-                    }),
-                    initial_delay_seconds: Some(30),
-                    period_seconds: Some(10),
-                    ..Default::default()
-                })
-            },
-            resources: Some(container_resources_requirements.clone()),
-            ..Default::default()
-        };
+            });
 
-        for micro_service in &chain_config.micro_service_list {
-            if micro_service.image.starts_with("consensus") {
-                if micro_service.image == CONSENSUS_RAFT {
-                    consensus_container.image = Some(format!(
-                        "{}/{}/{}:{}",
-                        &opts.docker_registry,
-                        &opts.docker_repo,
-                        &micro_service.image,
-                        &micro_service.tag
-                    ));
-                    consensus_container.command = Some(vec![
-                        "consensus".to_string(),
-                        "run".to_string(),
-                        "-c".to_string(),
-                        "/etc/cita-cloud/config/config.toml".to_string(),
-                        "--stdout".to_string(),
-                    ]);
-                } else if micro_service.image == CONSENSUS_OVERLORD {
-                    consensus_container.image = Some(format!(
-                        "{}/{}/{}:{}",
-                        &opts.docker_registry,
-                        &opts.docker_repo,
-                        &micro_service.image,
-                        &micro_service.tag
-                    ));
-                    consensus_container.command = Some(vec![
-                        "consensus".to_string(),
-                        "run".to_string(),
-                        "-c".to_string(),
-                        "/etc/cita-cloud/config/config.toml".to_string(),
-                        "-p".to_string(),
-                        "/mnt/private_key".to_string(),
-                    ]);
-                } else {
-                    panic!("Unknown consensus service!");
-                }
-            }
-        }
-
-        containers.push(consensus_container);
-
-        // executor
-        let mut executor_container = Container {
-            name: "executor".to_string(),
-            image_pull_policy: Some(opts.pull_policy.clone()),
-            ports: Some(vec![ContainerPort {
-                container_port: 50002,
-                name: Some("grpc".to_string()),
-                protocol: Some("TCP".to_string()),
-                ..Default::default()
-            }]),
-            volume_mounts: Some(vec![
-                VolumeMount {
-                    mount_path: "/data".to_string(),
-                    name: "datadir".to_string(),
-                    ..Default::default()
-                },
-                VolumeMount {
-                    mount_path: "/etc/cita-cloud/config".to_string(),
-                    name: "node-config".to_string(),
-                    ..Default::default()
-                },
-                VolumeMount {
-                    mount_path: "/etc/localtime".to_string(),
-                    name: "node-localtime".to_string(),
-                    ..Default::default()
-                },
-            ]),
-            working_dir: Some("/data".to_string()),
-            liveness_probe: if opts.disable_health_check {
-                None
-            } else {
-                Some(Probe {
-                    exec: Some(ExecAction {
-                        command: Some(vec![
-                            "grpc-health-probe".to_string(),
-                            "-addr=localhost:50002".to_string(),
-                        ]),
+            let env = kind.env(&ctx);
+            let env_from = match &opts.storage_env_secret {
+                Some(secret) if kind.service() == STORAGE => Some(vec![EnvFromSource {
+                    secret_ref: Some(SecretEnvSource {
+                        name: secret.clone(),
+                        ..Default::default()
                     }),
-                    initial_delay_seconds: Some(30),
-                    period_seconds: Some(10),
                     ..Default::default()
-                })
-            },
-            resources: Some(container_resources_requirements.clone()),
-            ..Default::default()
-        };
+                }]),
+                _ => None,
+            };
 
-        for micro_service in &chain_config.micro_service_list {
-            if micro_service.image.starts_with("executor") {
-                if micro_service.image == EXECUTOR_EVM {
-                    executor_container.image = Some(format!(
-                        "{}/{}/{}:{}",
-                        &opts.docker_registry,
-                        &opts.docker_repo,
-                        &micro_service.image,
-                        &micro_service.tag
-                    ));
-                    executor_container.command = Some(vec![
-                        "executor".to_string(),
-                        "run".to_string(),
-                        "-c".to_string(),
-                        "/etc/cita-cloud/config/config.toml".to_string(),
-                    ]);
+            containers.push(Container {
+                name: kind.service().to_string(),
+                image: Some(micro_service.image_ref(&opts.docker_registry, &opts.docker_repo)),
+                image_pull_policy: Some(opts.pull_policy.clone()),
+                command: Some(kind.command()),
+                ports: Some(kind.container_ports(&ctx)),
+                volume_mounts: Some(volume_mounts),
+                env: if env.is_empty() { None } else { Some(env) },
+                env_from,
+                working_dir: Some("/data".to_string()),
+                liveness_probe: if opts.disable_health_check {
+                    None
                 } else {
-                    panic!("Unknown executor service!");
-                }
-            }
-        }
-
-        containers.push(executor_container);
-
-        // storage
-        let mut storage_container = Container {
-            name: "storage".to_string(),
-            image_pull_policy: Some(opts.pull_policy.clone()),
-            ports: Some(vec![ContainerPort {
-                container_port: 50003,
-                name: Some("grpc".to_string()),
-                protocol: Some("TCP".to_string()),
-                ..Default::default()
-            }]),
-            volume_mounts: Some(vec![
-                VolumeMount {
-                    mount_path: "/data".to_string(),
-                    name: "datadir".to_string(),
-                    ..Default::default()
-                },
-                VolumeMount {
-                    mount_path: "/etc/cita-cloud/config".to_string(),
-                    name: "node-config".to_string(),
-                    ..Default::default()
+                    Some(kind.liveness_probe(&ctx))
                 },
-                VolumeMount {
-                    mount_path: "/etc/localtime".to_string(),
-                    name: "node-localtime".to_string(),
-                    ..Default::default()
-                },
-            ]),
-            working_dir: Some("/data".to_string()),
-            liveness_probe: if opts.disable_health_check {
-                None
-            } else {
-                Some(Probe {
-                    exec: Some(ExecAction {
-                        command: Some(vec![
-                            "grpc-health-probe".to_string(),
-                            "-addr=localhost:50003".to_string(),
-                        ]),
-                    }),
-                    initial_delay_seconds: Some(30),
-                    period_seconds: Some(10),
-                    ..Default::default()
-                })
-            },
-            resources: Some(container_resources_requirements.clone()),
-            ..Default::default()
-        };
-
-        for micro_service in &chain_config.micro_service_list {
-            if micro_service.image.starts_with("storage") {
-                if micro_service.image == STORAGE_OPENDAL {
-                    storage_container.image = Some(format!(
-                        "{}/{}/{}:{}",
-                        &opts.docker_registry,
-                        &opts.docker_repo,
-                        &micro_service.image,
-                        &micro_service.tag
-                    ));
-                    storage_container.command = Some(vec![
-                        "storage".to_string(),
-                        "run".to_string(),
-                        "-c".to_string(),
-                        "/etc/cita-cloud/config/config.toml".to_string(),
-                    ]);
-                } else {
-                    panic!("Unknown storage service!");
-                }
-            }
-        }
-
-        containers.push(storage_container);
-
-        // controller
-        let mut controller_container = Container {
-            name: "controller".to_string(),
-            image_pull_policy: Some(opts.pull_policy.clone()),
-            ports: Some(vec![ContainerPort {
-                container_port: 50004,
-                name: Some("grpc".to_string()),
-                protocol: Some("TCP".to_string()),
+                resources: Some(container_resources_requirements.clone()),
                 ..Default::default()
-            }]),
-            volume_mounts: Some(vec![
-                VolumeMount {
-                    mount_path: "/data".to_string(),
-                    name: "datadir".to_string(),
-                    ..Default::default()
-                },
-                VolumeMount {
-                    mount_path: "/etc/cita-cloud/config".to_string(),
-                    name: "node-config".to_string(),
-                    ..Default::default()
-                },
-                VolumeMount {
-                    mount_path: "/mnt".to_string(),
-                    name: "node-account".to_string(),
-                    ..Default::default()
-                },
-                VolumeMount {
-                    mount_path: "/etc/localtime".to_string(),
-                    name: "node-localtime".to_string(),
-                    ..Default::default()
-                },
-            ]),
-            working_dir: Some("/data".to_string()),
-            liveness_probe: if opts.disable_health_check {
-                None
-            } else {
-                Some(Probe {
-                    exec: Some(ExecAction {
-                        command: Some(vec![
-                            "grpc-health-probe".to_string(),
-                            "-addr=localhost:50004".to_string(),
-                        ]),
-                    }),
-                    initial_delay_seconds: Some(60),
-                    period_seconds: Some(10),
-                    ..Default::default()
-                })
-            },
-            resources: Some(container_resources_requirements),
-            ..Default::default()
-        };
-
-        for micro_service in &chain_config.micro_service_list {
-            if micro_service.image.starts_with("controller") {
-                if micro_service.image == CONTROLLER || micro_service.image == CONTROLLER_HSM {
-                    controller_container.image = Some(format!(
-                        "{}/{}/{}:{}",
-                        &opts.docker_registry,
-                        &opts.docker_repo,
-                        &micro_service.image,
-                        &micro_service.tag
-                    ));
-                    controller_container.command = Some(vec![
-                        "controller".to_string(),
-                        "run".to_string(),
-                        "-c".to_string(),
-                        "/etc/cita-cloud/config/config.toml".to_string(),
-                        "-p".to_string(),
-                        "/mnt/private_key".to_string(),
-                    ]);
-                } else {
-                    panic!("Unknown controller service!");
-                }
-            }
+            });
         }
 
-        containers.push(controller_container);
-
         // debug
         if opts.enable_debug {
             let mut debug_container = Container {
@@ -907,8 +602,8 @@ pub fn execute_update_yaml(opts: UpdateYamlOpts) -> Result<NodeK8sConfig, Error>
         svc_spec.ports = Some(vec![
             ServicePort {
                 name: Some("network".to_string()),
-                port: 40000,
-                target_port: Some(IntOrString::Int(40000)),
+                port: my_internal_port as i32,
+                target_port: Some(IntOrString::Int(my_internal_port as i32)),
                 protocol: Some(network_protocol.to_string()),
                 ..Default::default()
             },
@@ -939,153 +634,152 @@ pub fn execute_update_yaml(opts: UpdateYamlOpts) -> Result<NodeK8sConfig, Error>
     }
 
     {
-        // create external svc and endpoints for peers
+        // create external svc and endpoints for peers decided by topology of chain
         // ignore if same cluster and same namespace
         // if same cluster and different namespace, create external svc with peer svc cluster FQDN
         // if diffrent cluster and peer host is FQDN, create external svc with peer host
-        // if diffrent cluster and peer host is ip, create external svc map port to 40000 and create external endpoints
-        for node_network_address in &chain_config.node_network_address_list {
-            if node_network_address.domain != opts.domain {
-                let peer_cluster_name = &node_network_address.cluster;
-                let peer_name_space = &node_network_address.name_space;
-                let peer_domain = &node_network_address.domain;
-                let peer_host = &node_network_address.host;
-                let is_peer_host_ip =
-                    peer_host.parse::<Ipv4Addr>().is_ok() || peer_host.parse::<Ipv6Addr>().is_ok();
-                let peer_port = node_network_address.port;
-                let peer_svc_name =
-                    format!("{}-{}", &opts.chain_name, &node_network_address.domain);
-
-                let same_cluster = peer_cluster_name == my_cluster_name;
-                let same_name_space = peer_name_space == my_name_space;
-                match (same_cluster, same_name_space, is_peer_host_ip) {
-                    (true, true, _) => {}
-                    (true, false, _) => {
-                        let mut external_svc = Service::default();
-
-                        let metadata = ObjectMeta {
-                            name: Some(peer_svc_name.clone()),
-                            ..Default::default()
-                        };
-                        external_svc.metadata = metadata;
-
-                        let svc_spec = ServiceSpec {
-                            type_: Some("ExternalName".to_string()),
-                            external_name: Some(format!(
-                                "{}.{}.svc.cluster.local",
-                                &peer_svc_name, &peer_name_space
-                            )),
-                            ..Default::default()
-                        };
-                        external_svc.spec = Some(svc_spec);
-
-                        let yaml_file_name =
-                            format!("{}/{}-external-svc.yaml", &yamls_path, &peer_domain);
-                        write_file(
-                            serde_yaml::to_string(&external_svc).unwrap().as_bytes(),
-                            yaml_file_name,
-                        );
-                        node_k8s_config.external_svc.push(external_svc);
-                    }
-                    (false, _, false) => {
-                        let mut external_svc = Service::default();
+        // if diffrent cluster and peer host is ip, create external svc map peer internal port to peer port and create external endpoints
+        for node_network_address in chain_config.peers(&opts.domain) {
+            let peer_cluster_name = &node_network_address.cluster;
+            let peer_name_space = &node_network_address.name_space;
+            let peer_domain = &node_network_address.domain;
+            let peer_host = &node_network_address.host;
+            let is_peer_host_ip =
+                peer_host.parse::<Ipv4Addr>().is_ok() || peer_host.parse::<Ipv6Addr>().is_ok();
+            let peer_port = node_network_address.port;
+            let peer_protocol = node_network_address.protocol.k8s_protocol();
+            let peer_internal_port = chain_config.internal_port(node_network_address);
+            let peer_svc_name = format!("{}-{}", &opts.chain_name, &node_network_address.domain);
+
+            let same_cluster = peer_cluster_name == my_cluster_name;
+            let same_name_space = peer_name_space == my_name_space;
+            match (same_cluster, same_name_space, is_peer_host_ip) {
+                (true, true, _) => {}
+                (true, false, _) => {
+                    let mut external_svc = Service::default();
+
+                    let metadata = ObjectMeta {
+                        name: Some(peer_svc_name.clone()),
+                        ..Default::default()
+                    };
+                    external_svc.metadata = metadata;
+
+                    let svc_spec = ServiceSpec {
+                        type_: Some("ExternalName".to_string()),
+                        external_name: Some(format!(
+                            "{}.{}.svc.cluster.local",
+                            &peer_svc_name, &peer_name_space
+                        )),
+                        ..Default::default()
+                    };
+                    external_svc.spec = Some(svc_spec);
+
+                    let yaml_file_name =
+                        format!("{}/{}-external-svc.yaml", &yamls_path, &peer_domain);
+                    write_file(
+                        serde_yaml::to_string(&external_svc).unwrap().as_bytes(),
+                        yaml_file_name,
+                    );
+                    node_k8s_config.external_svc.push(external_svc);
+                }
+                (false, _, false) => {
+                    let mut external_svc = Service::default();
 
-                        let metadata = ObjectMeta {
-                            name: Some(peer_svc_name.clone()),
-                            ..Default::default()
-                        };
-                        external_svc.metadata = metadata;
+                    let metadata = ObjectMeta {
+                        name: Some(peer_svc_name.clone()),
+                        ..Default::default()
+                    };
+                    external_svc.metadata = metadata;
 
-                        let svc_spec = ServiceSpec {
-                            type_: Some("ExternalName".to_string()),
-                            external_name: Some(peer_host.clone()),
+                    let svc_spec = ServiceSpec {
+                        type_: Some("ExternalName".to_string()),
+                        external_name: Some(peer_host.clone()),
+                        ..Default::default()
+                    };
+                    external_svc.spec = Some(svc_spec);
+
+                    let yaml_file_name =
+                        format!("{}/{}-external-svc.yaml", &yamls_path, &peer_domain);
+                    write_file(
+                        serde_yaml::to_string(&external_svc).unwrap().as_bytes(),
+                        yaml_file_name,
+                    );
+                    node_k8s_config.external_svc.push(external_svc);
+                }
+                (false, _, true) => {
+                    let mut external_svc = Service::default();
+                    let metadata = ObjectMeta {
+                        name: Some(peer_svc_name.clone()),
+                        ..Default::default()
+                    };
+                    external_svc.metadata = metadata;
+
+                    let svc_spec = ServiceSpec {
+                        ports: Some(vec![ServicePort {
+                            name: Some("network".to_string()),
+                            port: peer_internal_port as i32,
+                            target_port: Some(IntOrString::Int(peer_port as i32)),
+                            protocol: Some(peer_protocol.to_string()),
                             ..Default::default()
-                        };
-                        external_svc.spec = Some(svc_spec);
-
-                        let yaml_file_name =
-                            format!("{}/{}-external-svc.yaml", &yamls_path, &peer_domain);
-                        write_file(
-                            serde_yaml::to_string(&external_svc).unwrap().as_bytes(),
-                            yaml_file_name,
-                        );
-                        node_k8s_config.external_svc.push(external_svc);
+                        }]),
+                        ..Default::default()
+                    };
+                    external_svc.spec = Some(svc_spec);
+
+                    let mut endpoint_slice = EndpointSlice::default();
+                    let mut metadata = ObjectMeta {
+                        name: Some(format!("{}-1", peer_svc_name)),
+                        ..Default::default()
+                    };
+
+                    let mut labels = BTreeMap::new();
+                    labels.insert(
+                        "kubernetes.io/service-name".to_string(),
+                        peer_svc_name.clone(),
+                    );
+                    metadata.labels = Some(labels);
+
+                    endpoint_slice.metadata = metadata;
+
+                    if peer_host.parse::<Ipv4Addr>().is_ok() {
+                        endpoint_slice.address_type = "IPv4".to_string();
+                    } else {
+                        endpoint_slice.address_type = "IPv6".to_string();
                     }
-                    (false, _, true) => {
-                        let mut external_svc = Service::default();
-                        let metadata = ObjectMeta {
-                            name: Some(peer_svc_name.clone()),
-                            ..Default::default()
-                        };
-                        external_svc.metadata = metadata;
-
-                        let svc_spec = ServiceSpec {
-                            ports: Some(vec![ServicePort {
-                                name: Some("network".to_string()),
-                                port: 40000,
-                                target_port: Some(IntOrString::Int(peer_port as i32)),
-                                protocol: Some(network_protocol.to_string()),
-                                ..Default::default()
-                            }]),
-                            ..Default::default()
-                        };
-                        external_svc.spec = Some(svc_spec);
 
-                        let mut endpoint_slice = EndpointSlice::default();
-                        let mut metadata = ObjectMeta {
-                            name: Some(format!("{}-1", peer_svc_name)),
-                            ..Default::default()
-                        };
-
-                        let mut labels = BTreeMap::new();
-                        labels.insert(
-                            "kubernetes.io/service-name".to_string(),
-                            peer_svc_name.clone(),
-                        );
-                        metadata.labels = Some(labels);
-
-                        endpoint_slice.metadata = metadata;
-
-                        if peer_host.parse::<Ipv4Addr>().is_ok() {
-                            endpoint_slice.address_type = "IPv4".to_string();
-                        } else {
-                            endpoint_slice.address_type = "IPv6".to_string();
-                        }
-
-                        endpoint_slice.ports = Some(vec![EndpointPort {
-                            port: Some(peer_port as i32),
-                            protocol: Some(network_protocol.to_string()),
-                            ..Default::default()
-                        }]);
+                    endpoint_slice.ports = Some(vec![EndpointPort {
+                        port: Some(peer_port as i32),
+                        protocol: Some(peer_protocol.to_string()),
+                        ..Default::default()
+                    }]);
 
-                        endpoint_slice.endpoints = vec![Endpoint {
-                            addresses: vec![peer_host.clone()],
-                            conditions: Some(EndpointConditions {
-                                ready: Some(true),
-                                ..Default::default()
-                            }),
+                    endpoint_slice.endpoints = vec![Endpoint {
+                        addresses: vec![peer_host.clone()],
+                        conditions: Some(EndpointConditions {
+                            ready: Some(true),
                             ..Default::default()
-                        }];
-
-                        let yaml_file_name =
-                            format!("{}/{}-external-svc.yaml", &yamls_path, &peer_domain);
-                        write_file(
-                            serde_yaml::to_string(&external_svc).unwrap().as_bytes(),
-                            yaml_file_name,
-                        );
-
-                        let yaml_file_name = format!(
-                            "{}/{}-external-endpointslice.yaml",
-                            &yamls_path, &peer_domain
-                        );
-                        write_file(
-                            serde_yaml::to_string(&endpoint_slice).unwrap().as_bytes(),
-                            yaml_file_name,
-                        );
-
-                        node_k8s_config.external_svc.push(external_svc);
-                        node_k8s_config.external_endpoints.push(endpoint_slice);
-                    }
+                        }),
+                        ..Default::default()
+                    }];
+
+                    let yaml_file_name =
+                        format!("{}/{}-external-svc.yaml", &yamls_path, &peer_domain);
+                    write_file(
+                        serde_yaml::to_string(&external_svc).unwrap().as_bytes(),
+                        yaml_file_name,
+                    );
+
+                    let yaml_file_name = format!(
+                        "{}/{}-external-endpointslice.yaml",
+                        &yamls_path, &peer_domain
+                    );
+                    write_file(
+                        serde_yaml::to_string(&endpoint_slice).unwrap().as_bytes(),
+                        yaml_file_name,
+                    );
+
+                    node_k8s_config.external_svc.push(external_svc);
+                    node_k8s_config.external_endpoints.push(endpoint_slice);
                 }
             }
         }
@@ -1125,5 +819,15 @@ pub fn execute_update_yaml(opts: UpdateYamlOpts) -> Result<NodeK8sConfig, Error>
         );
     }
 
+    // keep opts to regenerate yaml later, such as by set-image
+    write_toml(&opts, format!("{}/{}", &node_dir, UPDATE_YAML_OPTS_FILE));
+
+    audit(
+        &opts.config_dir,
+        &opts.chain_name,
+        "update-yaml",
+        &before_hash,
+    );
+
     Ok(node_k8s_config)
 }
@@ -12,8 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::config::chain_config::ChainConfig;
+use crate::config::chain_config::{check_node_list, ChainConfig, NodeNetworkAddress};
 use crate::config::node_config::NodeConfig;
+use crate::constant::SCHEMA_VERSION;
+use crate::migration::{migrate_chain_config, migrate_node_config, schema_version};
+use ophelia::{BlsSignatureVerify, HashValue, PrivateKey, Signature};
+use ophelia_blst::{BlsPrivateKey, BlsPublicKey, BlsSignature};
 use rcgen::{
     BasicConstraints, CertificateParams, CertificateSigningRequestParams, DistinguishedName,
     DnType, DnValue, IsCa, KeyPair,
@@ -22,7 +26,7 @@ use std::io::{Read, Write};
 use std::time::{SystemTime, UNIX_EPOCH};
 use std::{fs, io, path};
 use toml::de::Error;
-use toml::Value;
+use toml::{Table, Value};
 
 pub fn write_to_file<T: serde::Serialize>(content: T, path: impl AsRef<path::Path>, name: String) {
     let value = Value::try_from(content).unwrap();
@@ -40,16 +44,55 @@ pub fn write_to_file<T: serde::Serialize>(content: T, path: impl AsRef<path::Pat
     file.write_all(b"\n").unwrap();
 }
 
-pub fn read_chain_config(path: impl AsRef<path::Path>) -> Result<ChainConfig, Error> {
+// parse config file, config of old schema version is upgraded in memory only if migrate is set
+fn read_config_table(
+    path: &path::Path,
+    upgrade: fn(&mut Table) -> Result<u64, String>,
+    migrate: bool,
+) -> Result<Table, Error> {
     let buffer = std::fs::read_to_string(path)
         .unwrap_or_else(|err| panic!("Error while loading config: [{err}]"));
-    toml::from_str::<ChainConfig>(&buffer)
+    let mut table = buffer.parse::<Table>()?;
+    let version = schema_version(&table);
+    if !migrate && version < SCHEMA_VERSION {
+        return Err(<Error as serde::de::Error>::custom(format!(
+            "{} is schema version {version}, please run `cloud-config migrate` first",
+            path.display()
+        )));
+    }
+    upgrade(&mut table).map_err(<Error as serde::de::Error>::custom)?;
+    Ok(table)
+}
+
+fn parse_chain_config(path: &path::Path, migrate: bool) -> Result<ChainConfig, Error> {
+    read_config_table(path, migrate_chain_config, migrate)?
+        .try_into::<ChainConfig>()
+        .map(|mut chain_config| {
+            chain_config.fill_micro_service_roles();
+            chain_config
+        })
 }
 
+/// config of old schema version must be upgraded by migrate first
+pub fn read_chain_config(path: impl AsRef<path::Path>) -> Result<ChainConfig, Error> {
+    parse_chain_config(path.as_ref(), false)
+}
+
+/// config of old schema version must be upgraded by migrate first
 pub fn read_node_config(path: impl AsRef<path::Path>) -> Result<NodeConfig, Error> {
-    let buffer = std::fs::read_to_string(path)
-        .unwrap_or_else(|err| panic!("Error while loading config: [{err}]"));
-    toml::from_str::<NodeConfig>(&buffer)
+    read_config_table(path.as_ref(), migrate_node_config, false)?.try_into::<NodeConfig>()
+}
+
+/// config of old schema version is upgraded in memory,
+/// only for migrate and read-only checks which never write it back
+pub fn load_chain_config(path: impl AsRef<path::Path>) -> Result<ChainConfig, Error> {
+    parse_chain_config(path.as_ref(), true)
+}
+
+/// config of old schema version is upgraded in memory,
+/// only for migrate and read-only checks which never write it back
+pub fn load_node_config(path: impl AsRef<path::Path>) -> Result<NodeConfig, Error> {
+    read_config_table(path.as_ref(), migrate_node_config, true)?.try_into::<NodeConfig>()
 }
 
 pub fn write_toml<T: serde::Serialize>(content: T, path: impl AsRef<path::Path>) {
@@ -160,9 +203,71 @@ pub fn sign_csr(csr_pem: &str, ca_cert_pem: &str, ca_key_pem: &str) -> String {
     cert.pem()
 }
 
+pub fn crypto_hash(input: &[u8]) -> Vec<u8> {
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "sm")] {
+            let hash = crypto_sm::sm::hash_data(input);
+        } else if #[cfg(feature = "eth")] {
+            let hash = crypto_eth::eth::hash_data(input);
+        }
+    }
+    hash.to_vec()
+}
+
+/// sign msg(hash) with account private key, return signature bytes
+pub fn sign_message(private_key: &[u8], msg: &[u8]) -> Vec<u8> {
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "sm")] {
+            let public_key = crypto_sm::sm::sk2pk(private_key);
+            let signature = crypto_sm::sm::sign_message(&public_key, private_key, msg);
+        } else if #[cfg(feature = "eth")] {
+            let public_key = crypto_eth::eth::sk2pk(private_key);
+            let signature = crypto_eth::eth::sign_message(&public_key, private_key, msg);
+        }
+    }
+    match signature {
+        Ok(signature) => signature.to_vec(),
+        Err(_) => panic!("sign message failed"),
+    }
+}
+
+/// recover signer address from msg(hash) and signature
+pub fn recover_address(msg: &[u8], signature: &[u8]) -> Option<Vec<u8>> {
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "sm")] {
+            let public_key = crypto_sm::sm::recover_signature(msg, signature).ok()?;
+            let address = crypto_sm::sm::pk2address(&public_key);
+        } else if #[cfg(feature = "eth")] {
+            let public_key = crypto_eth::eth::recover_signature(msg, signature).ok()?;
+            let address = crypto_eth::eth::pk2address(&public_key);
+        }
+    }
+    Some(address.to_vec())
+}
+
+/// sign msg(hash) with bls private key of validator, used by consensus_overlord
+pub fn bls_sign_message(private_key: &[u8], msg: &[u8]) -> Vec<u8> {
+    let private_key = BlsPrivateKey::try_from(private_key).expect("invalid bls private key");
+    let msg = HashValue::try_from(msg).expect("invalid msg hash");
+    private_key.sign_message(&msg).to_bytes().to_vec()
+}
+
+/// verify bls signature of msg(hash) against validator address which is bls public key
+pub fn bls_verify(validator_address: &[u8], msg: &[u8], signature: &[u8]) -> bool {
+    let (Ok(public_key), Ok(msg), Ok(signature)) = (
+        BlsPublicKey::try_from(validator_address),
+        HashValue::try_from(msg),
+        BlsSignature::try_from(signature),
+    ) else {
+        return false;
+    };
+    let common_ref = "".to_string();
+    signature.verify(&msg, &public_key, &common_ref).is_ok()
+}
+
 pub fn find_micro_service(chain_config: &ChainConfig, service_name: &str) -> bool {
     for micro_service in &chain_config.micro_service_list {
-        if micro_service.image == service_name {
+        if micro_service.kind() == service_name {
             return true;
         }
     }
@@ -173,6 +278,18 @@ pub fn remove_0x(s: &str) -> &str {
     s.strip_prefix("0x").unwrap_or(s)
 }
 
+/// strip 0x of address, return reason if length or hex is invalid
+pub fn parse_address(s: &str) -> Result<&str, String> {
+    let addr = remove_0x(s);
+    if addr.len() != 40 && addr.len() != 96 {
+        return Err(format!("wrong length of address {s}"));
+    }
+    if hex::decode(addr).is_err() {
+        return Err(format!("invalid hex of address {s}"));
+    }
+    Ok(addr)
+}
+
 pub fn check_address(s: &str) -> &str {
     let addr = s.strip_prefix("0x").unwrap_or(s);
     if addr.len() != 40 && addr.len() != 96 {
@@ -181,6 +298,44 @@ pub fn check_address(s: &str) -> &str {
     addr
 }
 
+/// parse node network address, print reason if invalid
+pub fn parse_node(node: &str) -> Result<NodeNetworkAddress, crate::error::Error> {
+    node.parse::<NodeNetworkAddress>().map_err(|e| {
+        println!("{e}");
+        crate::error::Error::InvalidNodeAddress
+    })
+}
+
+/// load node list from --nodelist or --nodelist-file(yaml or json) and check it
+pub fn load_node_list(
+    node_list: &str,
+    node_list_file: Option<&str>,
+) -> Result<Vec<NodeNetworkAddress>, crate::error::Error> {
+    let node_list = match node_list_file {
+        Some(node_list_file) => {
+            // json is a subset of yaml
+            let content = read_file(node_list_file).map_err(|e| {
+                println!("read {node_list_file} failed: {e}");
+                crate::error::Error::FileNoFound
+            })?;
+            serde_yaml::from_str::<Vec<NodeNetworkAddress>>(&content).map_err(|e| {
+                println!("invalid node list file {node_list_file}: {e}");
+                crate::error::Error::InvalidNodeAddress
+            })?
+        }
+        None => node_list
+            .split(',')
+            .filter(|node| !node.trim().is_empty())
+            .map(parse_node)
+            .collect::<Result<Vec<NodeNetworkAddress>, crate::error::Error>>()?,
+    };
+    check_node_list(&node_list).map_err(|e| {
+        println!("{e}");
+        crate::error::Error::InvalidNodeAddress
+    })?;
+    Ok(node_list)
+}
+
 pub fn copy_dir_all(src: impl AsRef<path::Path>, dst: impl AsRef<path::Path>) -> io::Result<()> {
     let _ = fs::create_dir_all(&dst);
     for entry in fs::read_dir(src)? {